@@ -53,12 +53,21 @@ uniform vec3 cameraDirection;
 uniform vec3 cameraUp;
 uniform vec3 cameraRight;
 uniform vec2 screenResolution;
+uniform mat4 viewMatrix;         // World to camera space
+uniform mat4 projectionMatrix;   // Camera to clip space (FOV, aspect, near/far)
+uniform mat4 inverseView;
+uniform mat4 inverseProjection;
 uniform vec3 skycolor;
 uniform vec3 camera_velocity;
 uniform bool is_accumulation;
+uniform bool hasTarget;     // Whether the camera is aiming at a block
+uniform ivec3 targetBlock;  // Block that would be broken
+uniform ivec3 targetFace;   // Normal of the face a new block would be placed against
+uniform float breakProgress; // 0 when not breaking, rising to 1 as the target block breaks
 
 const int bounces = 3;
 const float pi = 3.1415926535897932385;
+const float CRACK_PIXELS = 8.0;
 
 // Random number functions from compute_shader_cubes.glsl
 float hash(float n) { return fract(sin(n) * 43758.5453123); }
@@ -156,19 +165,62 @@ vec3 calculate_light(vec3 ro, vec3 rd, inout float seed) {
     return light;
 }
 
+// Pixelated cracks spreading out from the middle of the block as breaking progresses
+float crackMask(vec3 local) {
+    if (breakProgress <= 0.0) return 0.0;
+    vec3 q = floor(clamp(local, 0.0, 0.999) * CRACK_PIXELS);
+    float h = fract(sin(dot(q, vec3(12.9898, 78.233, 37.719))) * 43758.5453);
+    return step(h * 0.6 + length(local - 0.5), breakProgress * 1.4);
+}
+
+// Draw the targeted voxel as a wireframe box over the scene and tint the placement face
+vec3 applyTargetOverlay(vec3 color, vec3 ro, vec3 rd) {
+    if (!hasTarget) return color;
+    
+    vec3 boxMin = vec3(targetBlock);
+    vec3 t1 = (boxMin - ro) / rd;
+    vec3 t2 = (boxMin + 1.0 - ro) / rd;
+    vec3 tmin = min(t1, t2);
+    vec3 tmax = max(t1, t2);
+    float tnear = max(max(tmin.x, tmin.y), tmin.z);
+    float tfar = min(min(tmax.x, tmax.y), tmax.z);
+    if (tnear > tfar || tfar < 0.0) return color;
+    
+    // Both the front and back edges are drawn, so the outline reads as a wireframe
+    float ts[2] = float[](max(tnear, 0.0), tfar);
+    for (int i = 0; i < 2; i++) {
+        vec3 local = ro + rd * ts[i] - boxMin;
+        vec3 edgeDist = min(local, 1.0 - local);
+        int nearEdges = int(edgeDist.x < 0.03) + int(edgeDist.y < 0.03) + int(edgeDist.z < 0.03);
+        if (nearEdges >= 2) return vec3(0.02);
+    }
+    
+    color = mix(color, vec3(0.05), 0.75 * crackMask(ro + rd * max(tnear, 0.0) - boxMin));
+    
+    vec3 entryNormal = -sign(rd) * step(tmin.yzx, tmin) * step(tmin.zxy, tmin);
+    if (ivec3(entryNormal) == targetFace) return mix(color, vec3(1.0), 0.25);
+    return color;
+}
+
+// Primary ray through a pixel, from the camera's inverse projection and view matrices
+vec3 cameraRay(ivec2 texel) {
+    vec2 ndc = (vec2(texel) + vec2(0.5)) / screenResolution * 2.0 - 1.0;
+    vec4 viewTarget = inverseProjection * vec4(ndc, 1.0, 1.0);
+    vec3 viewDir = normalize(viewTarget.xyz / viewTarget.w);
+    return normalize((inverseView * vec4(viewDir, 0.0)).xyz);
+}
+
 void main() {
     ivec2 texel_coords = ivec2(gl_GlobalInvocationID.xy);
-    vec2 uv = (vec2(texel_coords) + vec2(0.5)) / screenResolution * 2.0 - 1.0;
-    uv.x *= screenResolution.x / screenResolution.y;
-    
     // Ray setup matching compute_shader_cubes.glsl
     vec3 ro = cameraPosition;
-    vec3 rd = normalize(cameraDirection + uv.x * cameraRight + uv.y * cameraUp);
+    vec3 rd = cameraRay(texel_coords);
     
     // Random seed based on position and time
     float seed = float(texel_coords.x * 1973 + texel_coords.y * 9277) + currentTime;
     
     vec3 color = calculate_light(ro, rd, seed);
+    color = applyTargetOverlay(color, ro, rd);
     
     if (is_accumulation) {
         vec4 prev = imageLoad(screen, texel_coords);
@@ -14,7 +14,15 @@ uniform vec3 cameraDirection;
 uniform vec3 cameraUp;
 uniform vec3 cameraRight;
 uniform vec2 screenResolution;
+uniform mat4 viewMatrix;         // World to camera space
+uniform mat4 projectionMatrix;   // Camera to clip space (FOV, aspect, near/far)
+uniform mat4 inverseView;
+uniform mat4 inverseProjection;
 uniform ivec3 worldSize;  // Size of the world in chunks
+uniform bool hasTarget;     // Whether the camera is aiming at a block
+uniform ivec3 targetBlock;  // Block that would be broken
+uniform ivec3 targetFace;   // Normal of the face a new block would be placed against
+uniform float breakProgress; // 0 when not breaking, rising to 1 as the target block breaks
 
 // Voxel types
 #define AIR 0
@@ -23,6 +31,13 @@ uniform ivec3 worldSize;  // Size of the world in chunks
 #define STONE 3
 #define WOOD 4
 #define LEAVES 5
+#define LIGHT 6
+#define COAL_ORE 7
+#define IRON_ORE 8
+#define GOLD_ORE 9
+#define DIAMOND_ORE 10
+#define WATER 11
+#define PREVIEW 12  // Ghost block of a shape tool preview, never stored in the world
 
 // Ray marching parameters
 #define MAX_STEPS 2048  // High precision
@@ -38,8 +53,20 @@ uniform ivec3 worldSize;  // Size of the world in chunks
 #define AMBIENT_STRENGTH 0.5  // Simple ambient light
 #define DIFFUSE_STRENGTH 0.5  // Simple diffuse light
 
-// Function to get voxel type at a position
-float getVoxelSDF(vec3 pos, out int voxelType) {
+// Water parameters
+#define WATER_COLOR vec3(0.1, 0.35, 0.6)
+#define WATER_IOR 1.33          // Index of refraction of water
+#define WATER_ABSORPTION 0.35   // How quickly light fades with depth below the surface
+#define SKY_COLOR vec3(0.5, 0.8, 1.0)
+
+// Target highlight parameters
+#define OUTLINE_COLOR vec3(0.02)
+#define PLACE_FACE_TINT vec3(1.0)
+#define CRACK_COLOR vec3(0.05)
+#define CRACK_PIXELS 8.0
+
+// Function to get voxel type at a position (water is skipped when ignoreWater is set)
+float getVoxelSDF(vec3 pos, bool ignoreWater, out int voxelType) {
     // Get the base cube position
     vec3 basePos = floor(pos);
     vec3 fracPos = pos - basePos;
@@ -59,7 +86,7 @@ float getVoxelSDF(vec3 pos, out int voxelType) {
         
         if (index >= 0 && index < 3 * 3 * 16 * 16 * 16) {
             voxelType = voxels[index];
-            if (voxelType != AIR) {
+            if (voxelType != AIR && !(ignoreWater && voxelType == WATER)) {
                 // Simple distance field for cubes
                 vec3 center = basePos + 0.5;
                 float d = length(pos - center) - 0.5;
@@ -85,19 +112,29 @@ vec3 getVoxelColor(int voxelType) {
             return vec3(0.4, 0.2, 0.1);
         case LEAVES:
             return vec3(0.0, 0.5, 0.0);
+        case COAL_ORE:
+            return vec3(0.2, 0.2, 0.2);
+        case IRON_ORE:
+            return vec3(0.75, 0.6, 0.5);
+        case GOLD_ORE:
+            return vec3(0.95, 0.8, 0.2);
+        case DIAMOND_ORE:
+            return vec3(0.4, 0.9, 0.95);
+        case PREVIEW:
+            return vec3(0.3, 0.8, 1.0);
         default:
             return vec3(0.0, 0.0, 0.0);
     }
 }
 
 // Ray marching function with three-phase approach
-float rayMarch(vec3 ro, vec3 rd, out int hitType) {
+float rayMarch(vec3 ro, vec3 rd, bool ignoreWater, out int hitType) {
     float dO = 0.0;
     
     // First phase: use small fixed steps for close objects
     for (int i = 0; i < MAX_STEPS/3; i++) {
         vec3 p = ro + rd * dO;
-        float d = getVoxelSDF(p, hitType);
+        float d = getVoxelSDF(p, ignoreWater, hitType);
         
         if (d < SURF_DIST) {
             return dO;
@@ -115,7 +152,7 @@ float rayMarch(vec3 ro, vec3 rd, out int hitType) {
     // Second phase: use medium step size for medium distance objects
     for (int i = 0; i < MAX_STEPS/3; i++) {
         vec3 p = ro + rd * dO;
-        float d = getVoxelSDF(p, hitType);
+        float d = getVoxelSDF(p, ignoreWater, hitType);
         
         if (d < SURF_DIST) {
             return dO;
@@ -133,7 +170,7 @@ float rayMarch(vec3 ro, vec3 rd, out int hitType) {
     // Third phase: use smaller step size for distant objects
     for (int i = 0; i < MAX_STEPS/3; i++) {
         vec3 p = ro + rd * dO;
-        float d = getVoxelSDF(p, hitType);
+        float d = getVoxelSDF(p, ignoreWater, hitType);
         
         if (d < SURF_DIST) {
             return dO;
@@ -157,49 +194,107 @@ vec3 getNormal(vec3 p) {
     vec2 e = vec2(0.001, 0.0);  // Simple epsilon
     int dummy;
     return normalize(vec3(
-        getVoxelSDF(p + e.xyy, dummy) - getVoxelSDF(p - e.xyy, dummy),
-        getVoxelSDF(p + e.yxy, dummy) - getVoxelSDF(p - e.yxy, dummy),
-        getVoxelSDF(p + e.yyx, dummy) - getVoxelSDF(p - e.yyx, dummy)
+        getVoxelSDF(p + e.xyy, false, dummy) - getVoxelSDF(p - e.xyy, false, dummy),
+        getVoxelSDF(p + e.yxy, false, dummy) - getVoxelSDF(p - e.yxy, false, dummy),
+        getVoxelSDF(p + e.yyx, false, dummy) - getVoxelSDF(p - e.yyx, false, dummy)
+    ));
+}
+
+// Pixelated cracks spreading out from the middle of the block as breaking progresses
+float crackMask(vec3 local) {
+    if (breakProgress <= 0.0) return 0.0;
+    vec3 q = floor(clamp(local, 0.0, 0.999) * CRACK_PIXELS);
+    float h = fract(sin(dot(q, vec3(12.9898, 78.233, 37.719))) * 43758.5453);
+    return step(h * 0.6 + length(local - 0.5), breakProgress * 1.4);
+}
+
+// Outline the targeted block and tint the side a new block would be placed against
+vec3 applyTargetHighlight(vec3 color, vec3 p, vec3 normal, vec3 rd) {
+    if (!hasTarget || ivec3(floor(p)) != targetBlock) return color;
+    
+    // Dark rim where the surface turns away from the viewer
+    if (1.0 - abs(dot(normal, rd)) > 0.85) return OUTLINE_COLOR;
+    
+    color = mix(color, CRACK_COLOR, 0.75 * crackMask(p - vec3(targetBlock)));
+    if (dot(normal, vec3(targetFace)) > 0.7) return mix(color, PLACE_FACE_TINT, 0.35);
+    return mix(color, PLACE_FACE_TINT, 0.1);
+}
+
+// Shade an opaque hit point with simple lighting and fog
+vec3 shadeSurface(vec3 p, vec3 rd, int hitType, float d) {
+    vec3 normal = getNormal(p);
+    
+    // Simple lighting
+    vec3 lightDir = normalize(vec3(1.0, 1.0, 1.0));
+    float diff = max(dot(normal, lightDir), 0.0);
+    
+    // Basic ambient and diffuse lighting
+    vec3 ambient = vec3(AMBIENT_STRENGTH);
+    vec3 diffuse = vec3(DIFFUSE_STRENGTH) * diff;
+    
+    // Get base color and apply lighting
+    vec3 col = getVoxelColor(hitType) * (ambient + diffuse);
+    
+    // Simple fog with reduced density for better far visibility
+    float fog = 1.0 - exp(-d * 0.005);  // Further reduced fog density
+    return applyTargetHighlight(mix(col, SKY_COLOR, fog), p, normal, rd);
+}
+
+// Small animated ripples on the water surface
+vec3 getWaterNormal(vec3 p) {
+    float t = currentTime * 1.5;
+    return normalize(vec3(
+        sin(p.x * 3.0 + t) * 0.04,
+        1.0,
+        cos(p.z * 3.0 + t * 1.3) * 0.04
     ));
 }
 
+// Primary ray through a pixel, from the camera's inverse projection and view matrices
+vec3 cameraRay(ivec2 texel) {
+    vec2 ndc = (vec2(texel) + vec2(0.5)) / screenResolution * 2.0 - 1.0;
+    vec4 viewTarget = inverseProjection * vec4(ndc, 1.0, 1.0);
+    vec3 viewDir = normalize(viewTarget.xyz / viewTarget.w);
+    return normalize((inverseView * vec4(viewDir, 0.0)).xyz);
+}
+
 void main() {
     ivec2 texel_coords = ivec2(gl_GlobalInvocationID.xy);
-    vec2 uv = (vec2(texel_coords) + vec2(0.5)) / screenResolution * 2.0 - 1.0;
-    uv.x *= screenResolution.x / screenResolution.y;
-    
     // Ray setup
     vec3 ro = cameraPosition;
-    vec3 rd = normalize(cameraDirection + uv.x * cameraRight + uv.y * cameraUp);
+    vec3 rd = cameraRay(texel_coords);
     
     // Ray marching
     int hitType;
-    float d = rayMarch(ro, rd, hitType);
+    float d = rayMarch(ro, rd, false, hitType);
     
     // Calculate color
     vec3 col = vec3(0.0);
     
-    if (d < MAX_DIST) {
+    if (d < MAX_DIST && hitType == WATER) {
+        // Water is transparent: refract into it and march again ignoring water
         vec3 p = ro + rd * d;
-        vec3 normal = getNormal(p);
-        
-        // Simple lighting
-        vec3 lightDir = normalize(vec3(1.0, 1.0, 1.0));
-        float diff = max(dot(normal, lightDir), 0.0);
+        vec3 normal = getWaterNormal(p);
+        vec3 refracted = refract(rd, normal, 1.0 / WATER_IOR);
         
-        // Basic ambient and diffuse lighting
-        vec3 ambient = vec3(AMBIENT_STRENGTH);
-        vec3 diffuse = vec3(DIFFUSE_STRENGTH) * diff;
+        int underType;
+        float underD = rayMarch(p, refracted, true, underType);
+        vec3 under = underD < MAX_DIST ? shadeSurface(p + refracted * underD, refracted, underType, d + underD) : WATER_COLOR;
         
-        // Get base color and apply lighting
-        col = getVoxelColor(hitType) * (ambient + diffuse);
+        // Absorb light with depth and mix in a fresnel reflection of the sky
+        float absorption = 1.0 - exp(-underD * WATER_ABSORPTION);
+        vec3 waterCol = mix(under, WATER_COLOR, absorption);
+        float fresnel = pow(1.0 - max(dot(-rd, normal), 0.0), 5.0);
+        col = mix(waterCol, SKY_COLOR, fresnel);
         
-        // Simple fog with reduced density for better far visibility
-        float fog = 1.0 - exp(-d * 0.005);  // Further reduced fog density
-        col = mix(col, vec3(0.5, 0.8, 1.0), fog);
+        float fog = 1.0 - exp(-d * 0.005);
+        col = mix(col, SKY_COLOR, fog);
+        col = applyTargetHighlight(col, p, normal, rd);
+    } else if (d < MAX_DIST) {
+        col = shadeSurface(ro + rd * d, rd, hitType, d);
     } else {
         // Sky color
-        col = vec3(0.5, 0.8, 1.0);
+        col = SKY_COLOR;
     }
     
     // Output color
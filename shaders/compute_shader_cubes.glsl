@@ -14,7 +14,15 @@ uniform vec3 cameraDirection;
 uniform vec3 cameraUp;
 uniform vec3 cameraRight;
 uniform vec2 screenResolution;
+uniform mat4 viewMatrix;         // World to camera space
+uniform mat4 projectionMatrix;   // Camera to clip space (FOV, aspect, near/far)
+uniform mat4 inverseView;
+uniform mat4 inverseProjection;
 uniform ivec3 worldSize;  // Size of the world in chunks
+uniform bool hasTarget;     // Whether the camera is aiming at a block
+uniform ivec3 targetBlock;  // Block that would be broken
+uniform ivec3 targetFace;   // Normal of the face a new block would be placed against
+uniform float breakProgress; // 0 when not breaking, rising to 1 as the target block breaks
 
 // Voxel types
 #define AIR 0
@@ -24,6 +32,12 @@ uniform ivec3 worldSize;  // Size of the world in chunks
 #define WOOD 4
 #define LEAVES 5
 #define LIGHT 6
+#define COAL_ORE 7
+#define IRON_ORE 8
+#define GOLD_ORE 9
+#define DIAMOND_ORE 10
+#define WATER 11
+#define PREVIEW 12  // Ghost block of a shape tool preview, never stored in the world
 
 // Ray tracing parameters
 #define MAX_DIST 100.0
@@ -45,6 +59,18 @@ uniform ivec3 worldSize;  // Size of the world in chunks
 #define LIGHT_SMOOTHNESS 1.5    // Adjusted for smoother falloff
 #define SHADOW_DARKNESS 0.2     // New parameter for shadow darkness
 
+// Water parameters
+#define WATER_COLOR vec3(0.1, 0.35, 0.6)
+#define WATER_IOR 1.33          // Index of refraction of water
+#define WATER_ABSORPTION 0.35   // How quickly light fades with depth below the surface
+
+// Target highlight parameters
+#define OUTLINE_WIDTH 0.03
+#define OUTLINE_COLOR vec3(0.02)
+#define PLACE_FACE_TINT vec3(1.0)
+#define CRACK_COLOR vec3(0.05)
+#define CRACK_PIXELS 8.0
+
 // Sky parameters
 #define SKY_COLOR_TOP vec3(0.4, 0.6, 1.0)
 #define SKY_COLOR_BOTTOM vec3(0.7, 0.8, 1.0)
@@ -119,6 +145,16 @@ vec3 getVoxelColor(int voxelType) {
             return vec3(0.0, 0.5, 0.0);
         case LIGHT:
             return vec3(1.0, 0.9, 0.7);
+        case COAL_ORE:
+            return vec3(0.2, 0.2, 0.2);
+        case IRON_ORE:
+            return vec3(0.75, 0.6, 0.5);
+        case GOLD_ORE:
+            return vec3(0.95, 0.8, 0.2);
+        case DIAMOND_ORE:
+            return vec3(0.4, 0.9, 0.95);
+        case PREVIEW:
+            return vec3(0.3, 0.8, 1.0);
         default:
             return vec3(0.0);
     }
@@ -195,7 +231,7 @@ float calcSoftShadow(vec3 ro, vec3 rd, float mint, float maxt) {
         ivec3 voxelPos = ivec3(floor(pos));
         int voxel = getVoxelType(voxelPos);
         
-        if(voxel != AIR && !isEmissive(voxel)) {
+        if(voxel != AIR && voxel != WATER && voxel != PREVIEW && !isEmissive(voxel)) {
             float h = 0.2 + t * 0.05; // Adjusted height calculation
             float y = h*h/(2.0*ph);
             float d = sqrt(h*h-y*y);
@@ -230,7 +266,7 @@ float calcAO(vec3 pos, vec3 normal) {
         aopos += offset;
         
         int voxel = getVoxelType(ivec3(floor(aopos)));
-        if(voxel != AIR) occ += (h - 0.01) * scale;
+        if(voxel != AIR && voxel != WATER && voxel != PREVIEW) occ += (h - 0.01) * scale;
         scale *= 0.75;
     }
     
@@ -243,8 +279,8 @@ float calcEdge(vec3 normal, vec3 viewDir) {
     return smoothstep(0.0, 1.0, edge) * EDGE_STRENGTH;
 }
 
-// Ray tracing function with culling
-float rayTrace(vec3 ro, vec3 rd, out int hitType, out vec3 hitNormal, out vec3 hitPos) {
+// Ray tracing function with culling (water is passed through when ignoreWater is set)
+float rayTrace(vec3 ro, vec3 rd, bool ignoreWater, out int hitType, out vec3 hitNormal, out vec3 hitPos) {
     hitType = AIR;
     hitNormal = vec3(0.0);
     hitPos = vec3(0.0);
@@ -263,7 +299,7 @@ float rayTrace(vec3 ro, vec3 rd, out int hitType, out vec3 hitNormal, out vec3 h
         ivec3 voxelPos = ivec3(pos);
         int voxel = getVoxelType(voxelPos);
         
-        if(voxel != AIR) {
+        if(voxel != AIR && !(ignoreWater && voxel == WATER)) {
             float t_near, t_far;
             if(intersectBox(ro, rd, pos, pos + 1.0, t_near, t_far)) {
                 if(t_near < minDist) {
@@ -321,83 +357,141 @@ vec3 getSkyColor(vec3 rayDir) {
     return skyColor + sunColor + vec3(cloud * 0.2);
 }
 
-void main() {
-    ivec2 texel_coords = ivec2(gl_GlobalInvocationID.xy);
-    vec2 uv = (vec2(texel_coords) + vec2(0.5)) / screenResolution * 2.0 - 1.0;
-    uv.x *= screenResolution.x / screenResolution.y;
-    
-    // Setup view frustum planes
-    vec3 fc = cameraPosition + cameraDirection;
-    vec3 nc = normalize(cameraDirection);
-    vec3 rc = normalize(cameraRight);
-    vec3 uc = normalize(cameraUp);
+// Pixelated cracks spreading out from the middle of the block as breaking progresses
+float crackMask(vec3 local) {
+    if (breakProgress <= 0.0) return 0.0;
+    vec3 q = floor(clamp(local, 0.0, 0.999) * CRACK_PIXELS);
+    float h = fract(sin(dot(q, vec3(12.9898, 78.233, 37.719))) * 43758.5453);
+    return step(h * 0.6 + length(local - 0.5), breakProgress * 1.4);
+}
+
+// Outline the targeted block and tint the face a new block would be placed against
+vec3 applyTargetHighlight(vec3 color, vec3 p, ivec3 cell, vec3 normal) {
+    if (!hasTarget || cell != targetBlock) return color;
+    
+    // Distance to the nearest edge of the face, ignoring the axis along the normal
+    vec3 local = clamp(p - vec3(cell), 0.0, 1.0);
+    vec3 edgeDist = min(local, 1.0 - local) + abs(normal);
+    if (min(min(edgeDist.x, edgeDist.y), edgeDist.z) < OUTLINE_WIDTH) return OUTLINE_COLOR;
+    
+    color = mix(color, CRACK_COLOR, 0.75 * crackMask(local));
+    if (ivec3(round(normal)) == targetFace) return mix(color, PLACE_FACE_TINT, 0.25);
+    return color;
+}
+
+// Shade an opaque hit with sun, shadows, AO, nearby light blocks and fog
+vec3 shadeHit(vec3 ro, vec3 rd, float d, int hitType, vec3 hitNormal, vec3 hitPos) {
+    vec3 p = ro + rd * d;
+    
+    // Enhanced lighting calculation
+    vec3 lightDir = normalize(vec3(1.0, 0.4, 0.0));  // Sun direction
+    float diff = max(dot(hitNormal, lightDir), 0.0);
+    diff = pow(diff, 0.8); // Soften diffuse falloff
+    
+    float shadow = calcSoftShadow(p + hitNormal * SHADOW_BIAS, lightDir, 0.1, 40.0);
+    float ao = calcAO(p, hitNormal);
+    
+    // Get base color and emission
+    vec3 baseColor = getVoxelColor(hitType);
+    float emission = getEmissionStrength(hitType);
+    
+    // Calculate final color with enhanced lighting
+    vec3 finalColor = baseColor * (AMBIENT_STRENGTH * ao + DIFFUSE_STRENGTH * diff * shadow);
+    
+    // Add point lights from nearby light blocks with larger radius
+    vec3 accumLight = vec3(0.0);
+    for(int x = -3; x <= 3; x++) {
+        for(int y = -3; y <= 3; y++) {
+            for(int z = -3; z <= 3; z++) {
+                ivec3 checkPos = ivec3(hitPos) + ivec3(x, y, z);
+                int voxel = getVoxelType(checkPos);
+                if(voxel == LIGHT) {
+                    vec3 lightPos = vec3(checkPos) + vec3(0.5);
+                    vec3 lightContrib = calcPointLight(p, hitNormal, lightPos, getVoxelColor(LIGHT), LIGHT_INTENSITY);
+                    accumLight += lightContrib;
+                }
+            }
+        }
+    }
+    finalColor += baseColor * accumLight;
     
-    float aspect = screenResolution.x / screenResolution.y;
-    float fov = 0.7;  // ~70 degrees
+    // Add emission for emissive blocks with smooth falloff
+    if(isEmissive(hitType)) {
+        float viewFalloff = 1.0 - pow(length(rd) * 0.1, 2.0); // Quadratic falloff
+        finalColor += baseColor * emission * viewFalloff;
+    }
     
-    frustumPlanes[0] = vec4(nc, -dot(nc, cameraPosition));  // Near
-    frustumPlanes[1] = vec4(-nc, dot(nc, cameraPosition + nc * MAX_DIST));  // Far
-    frustumPlanes[2] = vec4(normalize(cross(uc, fc + rc * fov * aspect)), 0.0);  // Right
-    frustumPlanes[3] = vec4(normalize(cross(fc - rc * fov * aspect, uc)), 0.0);  // Left
-    frustumPlanes[4] = vec4(normalize(cross(rc, fc + uc * fov)), 0.0);  // Top
-    frustumPlanes[5] = vec4(normalize(cross(fc - uc * fov, rc)), 0.0);  // Bottom
+    // Enhanced fog with better distance falloff
+    float fog = 1.0 - exp(-d * 0.015);  // Reduced fog density further
+    finalColor = mix(finalColor, getSkyColor(rd), fog);
+    return applyTargetHighlight(finalColor, p, ivec3(hitPos), hitNormal);
+}
+
+// Small animated ripples on the water surface
+vec3 rippleNormal(vec3 p, vec3 normal) {
+    float t = currentTime * 1.5;
+    return normalize(normal + vec3(sin(p.x * 3.0 + t), 0.0, cos(p.z * 3.0 + t * 1.3)) * 0.04);
+}
+
+// Primary ray through a pixel, from the camera's inverse projection and view matrices
+vec3 cameraRay(ivec2 texel) {
+    vec2 ndc = (vec2(texel) + vec2(0.5)) / screenResolution * 2.0 - 1.0;
+    vec4 viewTarget = inverseProjection * vec4(ndc, 1.0, 1.0);
+    vec3 viewDir = normalize(viewTarget.xyz / viewTarget.w);
+    return normalize((inverseView * vec4(viewDir, 0.0)).xyz);
+}
+
+void main() {
+    ivec2 texel_coords = ivec2(gl_GlobalInvocationID.xy);
+    // View frustum planes, taken from the rows of the view-projection matrix
+    mat4 viewProjection = projectionMatrix * viewMatrix;
+    vec4 row0 = vec4(viewProjection[0][0], viewProjection[1][0], viewProjection[2][0], viewProjection[3][0]);
+    vec4 row1 = vec4(viewProjection[0][1], viewProjection[1][1], viewProjection[2][1], viewProjection[3][1]);
+    vec4 row2 = vec4(viewProjection[0][2], viewProjection[1][2], viewProjection[2][2], viewProjection[3][2]);
+    vec4 row3 = vec4(viewProjection[0][3], viewProjection[1][3], viewProjection[2][3], viewProjection[3][3]);
+    frustumPlanes[0] = row3 + row2;  // Near
+    frustumPlanes[1] = row3 - row2;  // Far
+    frustumPlanes[2] = row3 - row0;  // Right
+    frustumPlanes[3] = row3 + row0;  // Left
+    frustumPlanes[4] = row3 - row1;  // Top
+    frustumPlanes[5] = row3 + row1;  // Bottom
+    for (int i = 0; i < 6; i++) {
+        frustumPlanes[i] /= length(frustumPlanes[i].xyz);
+    }
     
     // Ray setup
     vec3 ro = cameraPosition;
-    vec3 rd = normalize(cameraDirection + uv.x * cameraRight + uv.y * cameraUp);
+    vec3 rd = cameraRay(texel_coords);
     
     // Ray tracing
     int hitType;
     vec3 hitNormal;
     vec3 hitPos;
-    float d = rayTrace(ro, rd, hitType, hitNormal, hitPos);
+    float d = rayTrace(ro, rd, false, hitType, hitNormal, hitPos);
     
     vec3 finalColor;
     
-    if(d < MAX_DIST) {
+    if(d < MAX_DIST && hitType == WATER) {
+        // Water is transparent: refract into it and trace again ignoring water
         vec3 p = ro + rd * d;
+        vec3 normal = rippleNormal(p, hitNormal);
+        vec3 refracted = refract(rd, normal, 1.0 / WATER_IOR);
+        vec3 under = p + refracted * EPSILON * 10.0;
         
-        // Enhanced lighting calculation
-        vec3 lightDir = normalize(vec3(1.0, 0.4, 0.0));  // Sun direction
-        float diff = max(dot(hitNormal, lightDir), 0.0);
-        diff = pow(diff, 0.8); // Soften diffuse falloff
-        
-        float shadow = calcSoftShadow(p + hitNormal * SHADOW_BIAS, lightDir, 0.1, 40.0);
-        float ao = calcAO(p, hitNormal);
-        
-        // Get base color and emission
-        vec3 baseColor = getVoxelColor(hitType);
-        float emission = getEmissionStrength(hitType);
-        
-        // Calculate final color with enhanced lighting
-        finalColor = baseColor * (AMBIENT_STRENGTH * ao + DIFFUSE_STRENGTH * diff * shadow);
-        
-        // Add point lights from nearby light blocks with larger radius
-        vec3 accumLight = vec3(0.0);
-        for(int x = -3; x <= 3; x++) {
-            for(int y = -3; y <= 3; y++) {
-                for(int z = -3; z <= 3; z++) {
-                    ivec3 checkPos = ivec3(hitPos) + ivec3(x, y, z);
-                    int voxel = getVoxelType(checkPos);
-                    if(voxel == LIGHT) {
-                        vec3 lightPos = vec3(checkPos) + vec3(0.5);
-                        vec3 lightContrib = calcPointLight(p, hitNormal, lightPos, getVoxelColor(LIGHT), LIGHT_INTENSITY);
-                        accumLight += lightContrib;
-                    }
-                }
-            }
-        }
-        finalColor += baseColor * accumLight;
-        
-        // Add emission for emissive blocks with smooth falloff
-        if(isEmissive(hitType)) {
-            float viewFalloff = 1.0 - pow(length(rd) * 0.1, 2.0); // Quadratic falloff
-            finalColor += baseColor * emission * viewFalloff;
-        }
+        int underType;
+        vec3 underNormal;
+        vec3 underPos;
+        float underD = rayTrace(under, refracted, true, underType, underNormal, underPos);
+        vec3 underColor = underD < MAX_DIST ? shadeHit(under, refracted, underD, underType, underNormal, underPos) : WATER_COLOR;
         
-        // Enhanced fog with better distance falloff
-        float fog = 1.0 - exp(-d * 0.015);  // Reduced fog density further
-        finalColor = mix(finalColor, getSkyColor(rd), fog);
+        // Absorb light with depth and mix in a fresnel reflection of the sky
+        float absorption = 1.0 - exp(-underD * WATER_ABSORPTION);
+        vec3 waterColor = mix(underColor, WATER_COLOR, absorption);
+        float fresnel = pow(1.0 - max(dot(-rd, normal), 0.0), 5.0);
+        finalColor = mix(waterColor, getSkyColor(reflect(rd, normal)), fresnel);
+        finalColor = applyTargetHighlight(finalColor, p, ivec3(hitPos), hitNormal);
+    } else if(d < MAX_DIST) {
+        finalColor = shadeHit(ro, rd, d, hitType, hitNormal, hitPos);
     } else {
         finalColor = getSkyColor(rd);
     }
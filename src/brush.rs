@@ -0,0 +1,285 @@
+use crate::placement::{self, Aabb, PlacementError, PlacementRules};
+use crate::world_edit::WorldEdit;
+use crate::{RaycastHit, VoxelType, World};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BrushShape {
+    Single,
+    Cube,
+    Sphere,
+    Cylinder, // Upright, as tall as it is wide
+    Disc,     // One block thick, lying flat against the hit face
+}
+
+impl BrushShape {
+    pub const ALL: [BrushShape; 5] = [
+        BrushShape::Single,
+        BrushShape::Cube,
+        BrushShape::Sphere,
+        BrushShape::Cylinder,
+        BrushShape::Disc,
+    ];
+}
+
+// Cells covered by a brush centered on `center`; `normal` orients the disc
+pub fn brush_cells(shape: BrushShape, radius: i32, center: (i32, i32, i32), normal: (i32, i32, i32)) -> Vec<(i32, i32, i32)> {
+    if shape == BrushShape::Single || radius <= 0 {
+        return vec![center];
+    }
+
+    let round_radius = (radius as f32 + 0.5) * (radius as f32 + 0.5);
+    let in_circle = |a: i32, b: i32| ((a * a + b * b) as f32) <= round_radius;
+
+    // Axis the disc is flattened along (vertical if the face is unknown)
+    let disc_axis = if normal.0 != 0 {
+        0
+    } else if normal.2 != 0 {
+        2
+    } else {
+        1
+    };
+
+    let mut cells = Vec::new();
+    for dx in -radius..=radius {
+        for dy in -radius..=radius {
+            for dz in -radius..=radius {
+                let inside = match shape {
+                    BrushShape::Single => dx == 0 && dy == 0 && dz == 0,
+                    BrushShape::Cube => true,
+                    BrushShape::Sphere => ((dx * dx + dy * dy + dz * dz) as f32) <= round_radius,
+                    BrushShape::Cylinder => in_circle(dx, dz),
+                    BrushShape::Disc => match disc_axis {
+                        0 => dx == 0 && in_circle(dy, dz),
+                        2 => dz == 0 && in_circle(dx, dy),
+                        _ => dy == 0 && in_circle(dx, dz),
+                    },
+                };
+                if inside {
+                    cells.push((center.0 + dx, center.1 + dy, center.2 + dz));
+                }
+            }
+        }
+    }
+    cells
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StrokeAction {
+    Place(VoxelType),
+    Break,
+}
+
+// One press-drag-release of the place or break button, collected into a single edit
+pub struct BrushStroke {
+    action: StrokeAction,
+    shape: BrushShape,
+    radius: i32,
+    edit: WorldEdit,
+    last_center: Option<(i32, i32, i32)>,
+    plane: Option<(usize, i32)>, // Placement layer (axis, coordinate) fixed by the first dab
+}
+
+impl BrushStroke {
+    pub fn new(action: StrokeAction, shape: BrushShape, radius: i32) -> Self {
+        Self {
+            action,
+            shape,
+            radius,
+            edit: WorldEdit::new(),
+            last_center: None,
+            plane: None,
+        }
+    }
+
+    pub fn action(&self) -> StrokeAction {
+        self.action
+    }
+
+    // Apply the brush at a raycast hit; repeated hits on the same cell are ignored.
+    // Fails with the first rejection if placing didn't manage to put down any block.
+    pub fn paint(
+        &mut self,
+        world: &mut World,
+        hit: &RaycastHit,
+        target_liquids: bool,
+        rules: &PlacementRules,
+        blockers: &[Aabb],
+    ) -> Result<(), PlacementError> {
+        let center = match self.action {
+            StrokeAction::Place(_) => {
+                if hit.normal == (0, 0, 0) {
+                    return Ok(());
+                }
+                hit.place
+            }
+            StrokeAction::Break => hit.voxel,
+        };
+        if self.last_center == Some(center) {
+            return Ok(());
+        }
+
+        // Keep placement strokes on the layer they started on, so dragging
+        // doesn't build a column towards the camera
+        if let StrokeAction::Place(_) = self.action {
+            let axis = if hit.normal.0 != 0 {
+                0
+            } else if hit.normal.1 != 0 {
+                1
+            } else {
+                2
+            };
+            let coordinate = [center.0, center.1, center.2][axis];
+            match self.plane {
+                Some(plane) if plane != (axis, coordinate) => return Ok(()),
+                Some(_) => {}
+                None => self.plane = Some((axis, coordinate)),
+            }
+        }
+        self.last_center = Some(center);
+
+        let cells = brush_cells(self.shape, self.radius, center, hit.normal);
+        match self.action {
+            StrokeAction::Place(voxel_type) => {
+                let mut remaining: Vec<_> = cells
+                    .into_iter()
+                    .filter(|&(x, y, z)| !world.get_voxel(x, y, z).is_solid())
+                    .collect();
+                let mut placed = false;
+                let mut first_error = None;
+
+                // Blocks of the brush can support each other, so keep going while any get placed
+                loop {
+                    let before = remaining.len();
+                    let edit = &mut self.edit;
+                    remaining.retain(|&cell| match placement::validate(world, rules, cell, voxel_type, blockers) {
+                        Ok(()) => {
+                            edit.set(world, cell, voxel_type);
+                            placed = true;
+                            false
+                        }
+                        Err(error) => {
+                            first_error.get_or_insert(error);
+                            true
+                        }
+                    });
+                    if remaining.is_empty() || remaining.len() == before {
+                        break;
+                    }
+                }
+
+                match first_error {
+                    Some(error) if !placed => Err(error),
+                    _ => Ok(()),
+                }
+            }
+            StrokeAction::Break => {
+                for (x, y, z) in cells {
+                    let current = world.get_voxel(x, y, z);
+                    if current.is_solid() || (target_liquids && current.is_liquid()) {
+                        self.edit.set(world, (x, y, z), VoxelType::Air);
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn finish(self) -> WorldEdit {
+        self.edit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world_edit::EditHistory;
+
+    fn hit_on_top(voxel: (i32, i32, i32), voxel_type: VoxelType) -> RaycastHit {
+        RaycastHit {
+            voxel,
+            voxel_type,
+            normal: (0, 1, 0),
+            distance: 1.0,
+            place: (voxel.0, voxel.1 + 1, voxel.2),
+        }
+    }
+
+    fn rules() -> PlacementRules {
+        PlacementRules { require_support: false, ..PlacementRules::new() }
+    }
+
+    #[test]
+    fn brush_cell_counts() {
+        let center = (4, 4, 4);
+        assert_eq!(brush_cells(BrushShape::Single, 3, center, (0, 1, 0)), vec![center]);
+        assert_eq!(brush_cells(BrushShape::Cube, 2, center, (0, 1, 0)).len(), 125);
+        // Radius 2 circles are 5x5 squares without their corners; the cylinder stacks five
+        assert_eq!(brush_cells(BrushShape::Disc, 2, center, (0, 1, 0)).len(), 21);
+        assert_eq!(brush_cells(BrushShape::Cylinder, 2, center, (0, 1, 0)).len(), 105);
+        let sphere = brush_cells(BrushShape::Sphere, 2, center, (0, 1, 0));
+        assert_eq!(sphere.len(), 81);
+        assert!(sphere.contains(&(6, 4, 4)) && !sphere.contains(&(6, 6, 4)));
+    }
+
+    #[test]
+    fn disc_lies_flat_against_the_hit_face() {
+        let center = (0, 5, 0);
+        for (normal, axis) in [((1, 0, 0), 0), ((0, -1, 0), 1), ((0, 0, 1), 2)] {
+            for cell in brush_cells(BrushShape::Disc, 2, center, normal) {
+                let coordinates = [cell.0, cell.1, cell.2];
+                assert_eq!(coordinates[axis], [0, 5, 0][axis]);
+            }
+        }
+    }
+
+    #[test]
+    fn placing_stroke_stays_on_its_layer_and_undoes_as_one() {
+        let mut world = World::new();
+        for x in 0..4 {
+            world.set_voxel(x, 1, 0, VoxelType::Stone);
+        }
+        world.set_voxel(2, 2, 0, VoxelType::Stone); // A bump the stroke is dragged over
+        let mut stroke = BrushStroke::new(StrokeAction::Place(VoxelType::Wood), BrushShape::Single, 1);
+        stroke.paint(&mut world, &hit_on_top((0, 1, 0), VoxelType::Stone), false, &rules(), &[]).unwrap();
+        stroke.paint(&mut world, &hit_on_top((1, 1, 0), VoxelType::Stone), false, &rules(), &[]).unwrap();
+        stroke.paint(&mut world, &hit_on_top((2, 2, 0), VoxelType::Stone), false, &rules(), &[]).unwrap();
+        assert_eq!(world.get_voxel(0, 2, 0), VoxelType::Wood);
+        assert_eq!(world.get_voxel(1, 2, 0), VoxelType::Wood);
+        assert_eq!(world.get_voxel(2, 3, 0), VoxelType::Air);
+
+        let mut history = EditHistory::new();
+        history.push(stroke.finish());
+        assert!(history.undo(&mut world));
+        assert_eq!(world.get_voxel(0, 2, 0), VoxelType::Air);
+        assert_eq!(world.get_voxel(1, 2, 0), VoxelType::Air);
+        assert!(history.redo(&mut world));
+        assert_eq!(world.get_voxel(1, 2, 0), VoxelType::Wood);
+    }
+
+    #[test]
+    fn breaking_leaves_water_alone_unless_targeted() {
+        let mut world = World::new();
+        world.set_voxel(0, 0, 0, VoxelType::Stone);
+        world.set_voxel(1, 0, 0, VoxelType::Water);
+        let hit = hit_on_top((0, 0, 0), VoxelType::Stone);
+
+        let mut stroke = BrushStroke::new(StrokeAction::Break, BrushShape::Cube, 1);
+        stroke.paint(&mut world, &hit, false, &rules(), &[]).unwrap();
+        assert_eq!(world.get_voxel(0, 0, 0), VoxelType::Air);
+        assert_eq!(world.get_voxel(1, 0, 0), VoxelType::Water);
+
+        let mut stroke = BrushStroke::new(StrokeAction::Break, BrushShape::Cube, 1);
+        stroke.paint(&mut world, &hit, true, &rules(), &[]).unwrap();
+        assert_eq!(world.get_voxel(1, 0, 0), VoxelType::Air);
+    }
+
+    #[test]
+    fn placing_reports_why_nothing_was_placed() {
+        let mut world = World::new();
+        world.set_voxel(0, 15, 0, VoxelType::Stone);
+        let mut stroke = BrushStroke::new(StrokeAction::Place(VoxelType::Wood), BrushShape::Single, 1);
+        let result = stroke.paint(&mut world, &hit_on_top((0, 15, 0), VoxelType::Stone), false, &rules(), &[]);
+        assert_eq!(result, Err(PlacementError::TooHigh));
+        assert!(stroke.finish().is_empty());
+    }
+}
@@ -0,0 +1,366 @@
+use std::fmt;
+
+// Recorded camera pose along a flythrough
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Keyframe {
+    pub position: (f32, f32, f32),
+    pub yaw: f32,
+    pub pitch: f32,
+    pub fov: f32,
+}
+
+impl Keyframe {
+    fn values(&self) -> [f32; 6] {
+        [self.position.0, self.position.1, self.position.2, self.yaw, self.pitch, self.fov]
+    }
+
+    fn from_values(v: [f32; 6]) -> Self {
+        Self { position: (v[0], v[1], v[2]), yaw: v[3], pitch: v[4], fov: v[5] }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Interpolation {
+    CatmullRom, // Uniform Catmull-Rom through every keyframe
+    Bezier,     // Cubic Bezier segments with handles sized to the neighbouring segments
+}
+
+impl Interpolation {
+    pub const ALL: [Interpolation; 2] = [Interpolation::CatmullRom, Interpolation::Bezier];
+
+    fn name(self) -> &'static str {
+        match self {
+            Interpolation::CatmullRom => "catmull_rom",
+            Interpolation::Bezier => "bezier",
+        }
+    }
+}
+
+// Samples per segment used to measure the path, so playback can move at a constant speed
+const SAMPLES_PER_SEGMENT: usize = 32;
+
+// Fastest playback turns and zooms, so a pan or zoom in place still takes time to play
+const MAX_TURN_RATE: f32 = 60.0; // Degrees of yaw or pitch per second
+const MAX_ZOOM_RATE: f32 = 20.0; // Degrees of field of view per second
+
+pub struct CameraPath {
+    pub keyframes: Vec<Keyframe>,
+    pub interpolation: Interpolation,
+    pub speed: f32, // Playback speed in blocks per second
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum PathError {
+    BadLine(usize),          // 1-based line number that couldn't be read
+    UnknownInterpolation(String),
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathError::BadLine(line) => write!(f, "Camera path line {} is not valid", line),
+            PathError::UnknownInterpolation(name) => write!(f, "Unknown interpolation \"{}\"", name),
+        }
+    }
+}
+
+impl CameraPath {
+    pub fn new() -> Self {
+        Self {
+            keyframes: Vec::new(),
+            interpolation: Interpolation::CatmullRom,
+            speed: 4.0,
+        }
+    }
+
+    // Control points of the cubic Bezier between keyframes `i` and `i + 1`
+    fn segment(&self, i: usize) -> [[f32; 6]; 4] {
+        let last = self.keyframes.len() - 1;
+        let key = |k: usize| self.keyframes[k.min(last)].values();
+        let p0 = key(i.saturating_sub(1));
+        let p1 = key(i);
+        let p2 = key(i + 1);
+        let p3 = key(i + 2);
+
+        // Tangents follow the neighbours on either side. Catmull-Rom halves them; the Bezier
+        // mode weights them by segment length so unevenly spaced keys don't overshoot.
+        let (w1, w2) = match self.interpolation {
+            Interpolation::CatmullRom => (0.5, 0.5),
+            Interpolation::Bezier => {
+                let (d01, d12, d23) = (distance(&p0, &p1), distance(&p1, &p2), distance(&p2, &p3));
+                let weight = |near: f32, far: f32| if near + far > 0.0 { near / (near + far) } else { 0.5 };
+                (weight(d12, d01), weight(d12, d23))
+            }
+        };
+        let mut b1 = p1;
+        let mut b2 = p2;
+        for c in 0..6 {
+            b1[c] += (p2[c] - p0[c]) * w1 / 3.0;
+            b2[c] -= (p3[c] - p1[c]) * w2 / 3.0;
+        }
+        [p1, b1, b2, p2]
+    }
+
+    // Pose at parameter `t` (0..=1) along segment `i`
+    fn evaluate(&self, i: usize, t: f32) -> [f32; 6] {
+        let [p0, p1, p2, p3] = self.segment(i);
+        let u = 1.0 - t;
+        let mut out = [0.0; 6];
+        for c in 0..6 {
+            out[c] = u * u * u * p0[c] + 3.0 * u * u * t * p1[c] + 3.0 * u * t * t * p2[c] + t * t * t * p3[c];
+        }
+        out
+    }
+
+    // Shortest time segment `i` may take to play, given how far it turns and zooms
+    fn min_segment_time(&self, i: usize) -> f32 {
+        let (a, b) = (self.keyframes[i], self.keyframes[i + 1]);
+        let turn = (b.yaw - a.yaw).abs().max((b.pitch - a.pitch).abs());
+        (turn / MAX_TURN_RATE).max((b.fov - a.fov).abs() / MAX_ZOOM_RATE)
+    }
+
+    // Running distance along the path at each measured sample. Segments too short for their
+    // turn or zoom are stretched, as if the camera travelled that far at the path's speed.
+    fn arc_lengths(&self) -> Vec<f32> {
+        let mut lengths = vec![0.0];
+        for i in 0..self.keyframes.len() - 1 {
+            let mut previous = self.keyframes[i].values();
+            let steps: Vec<f32> = (1..=SAMPLES_PER_SEGMENT)
+                .map(|s| {
+                    let point = self.evaluate(i, s as f32 / SAMPLES_PER_SEGMENT as f32);
+                    let step = distance(&previous, &point);
+                    previous = point;
+                    step
+                })
+                .collect();
+            let travelled: f32 = steps.iter().sum();
+            let minimum = self.speed * self.min_segment_time(i);
+            for step in steps {
+                let step = if travelled >= minimum {
+                    step
+                } else if travelled > 0.0 {
+                    step * minimum / travelled
+                } else {
+                    minimum / SAMPLES_PER_SEGMENT as f32
+                };
+                let total = lengths.last().unwrap() + step;
+                lengths.push(total);
+            }
+        }
+        lengths
+    }
+
+    // Shift each keyframe's yaw by whole turns to within half a turn of the one before, so
+    // playback turns the short way round
+    pub fn unwrap_yaws(&mut self) {
+        for i in 1..self.keyframes.len() {
+            let previous = self.keyframes[i - 1].yaw;
+            let yaw = &mut self.keyframes[i].yaw;
+            *yaw = previous + (*yaw - previous + 180.0).rem_euclid(360.0) - 180.0;
+        }
+    }
+
+    // Length of the path in blocks, counting the stretch given to turns and zooms
+    pub fn length(&self) -> f32 {
+        if self.keyframes.len() < 2 {
+            return 0.0;
+        }
+        *self.arc_lengths().last().unwrap()
+    }
+
+    // Distance along the path at which each keyframe is reached
+    pub fn keyframe_distances(&self) -> Vec<f32> {
+        if self.keyframes.is_empty() {
+            return Vec::new();
+        }
+        let lengths = if self.keyframes.len() < 2 { vec![0.0] } else { self.arc_lengths() };
+        lengths.iter().step_by(SAMPLES_PER_SEGMENT).copied().collect()
+    }
+
+    // Pose `distance` blocks along the path, clamped to its ends
+    pub fn sample(&self, distance: f32) -> Option<Keyframe> {
+        match self.keyframes.len() {
+            0 => return None,
+            1 => return Some(self.keyframes[0]),
+            _ => {}
+        }
+        let lengths = self.arc_lengths();
+        let total = *lengths.last().unwrap();
+        let distance = distance.max(0.0).min(total);
+
+        // Find the measured sample span containing the distance and interpolate inside it
+        let index = match lengths.iter().position(|&l| l >= distance) {
+            Some(0) | None => 0,
+            Some(index) => index - 1,
+        };
+        let span = lengths[index + 1] - lengths[index];
+        let fraction = if span > 0.0 { (distance - lengths[index]) / span } else { 0.0 };
+        let segment = index / SAMPLES_PER_SEGMENT;
+        let t = ((index % SAMPLES_PER_SEGMENT) as f32 + fraction) / SAMPLES_PER_SEGMENT as f32;
+        Some(Keyframe::from_values(self.evaluate(segment, t)))
+    }
+
+    // Plain text: one setting or keyframe per line, `#` starts a comment
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# Camera path: key x y z yaw pitch fov\n");
+        text += &format!("interpolation {}\n", self.interpolation.name());
+        text += &format!("speed {}\n", self.speed);
+        for key in &self.keyframes {
+            let (x, y, z) = key.position;
+            text += &format!("key {} {} {} {} {} {}\n", x, y, z, key.yaw, key.pitch, key.fov);
+        }
+        text
+    }
+
+    pub fn from_text(text: &str) -> Result<CameraPath, PathError> {
+        let mut path = CameraPath::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            let mut words = line.split_whitespace();
+            let bad_line = PathError::BadLine(number + 1);
+            match words.next() {
+                None => {}
+                Some("interpolation") => {
+                    let name = words.next().ok_or(bad_line)?;
+                    path.interpolation = Interpolation::ALL
+                        .iter()
+                        .copied()
+                        .find(|i| i.name() == name)
+                        .ok_or_else(|| PathError::UnknownInterpolation(name.to_string()))?;
+                }
+                Some("speed") => {
+                    // A zero, negative or NaN speed would stall or reverse playback
+                    path.speed = words
+                        .next()
+                        .and_then(|w| w.parse::<f32>().ok())
+                        .filter(|speed| speed.is_finite() && *speed > 0.0)
+                        .ok_or(bad_line)?;
+                }
+                Some("key") => {
+                    let values: Vec<f32> = words.map(|w| w.parse()).collect::<Result<_, _>>().map_err(|_| bad_line.clone())?;
+                    if values.len() != 6 {
+                        return Err(bad_line);
+                    }
+                    path.keyframes.push(Keyframe::from_values([values[0], values[1], values[2], values[3], values[4], values[5]]));
+                }
+                Some(_) => return Err(bad_line),
+            }
+        }
+        path.unwrap_yaws();
+        Ok(path)
+    }
+}
+
+// Distance between two poses, measured on position only
+fn distance(a: &[f32; 6], b: &[f32; 6]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(x: f32, y: f32, z: f32, yaw: f32) -> Keyframe {
+        Keyframe { position: (x, y, z), yaw, pitch: -10.0, fov: 70.0 }
+    }
+
+    fn zigzag(interpolation: Interpolation) -> CameraPath {
+        let mut path = CameraPath::new();
+        path.interpolation = interpolation;
+        path.keyframes = vec![
+            key(0.0, 5.0, 0.0, 0.0),
+            key(10.0, 5.0, 0.0, 90.0),
+            key(12.0, 8.0, 6.0, 180.0),
+            key(30.0, 6.0, 6.0, 200.0),
+        ];
+        path
+    }
+
+    #[test]
+    fn passes_through_every_keyframe() {
+        for interpolation in Interpolation::ALL {
+            let path = zigzag(interpolation);
+            for (k, distance) in path.keyframe_distances().into_iter().enumerate() {
+                let pose = path.sample(distance).unwrap();
+                let expected = path.keyframes[k];
+                assert!(distance_between(pose.position, expected.position) < 1e-2);
+                assert!((pose.yaw - expected.yaw).abs() < 0.5);
+            }
+        }
+    }
+
+    #[test]
+    fn playback_moves_at_a_constant_speed() {
+        for interpolation in Interpolation::ALL {
+            let path = zigzag(interpolation);
+            let step = 0.25;
+            let mut previous = path.sample(0.0).unwrap().position;
+            let mut travelled = step;
+            while travelled < path.length() {
+                let next = path.sample(travelled).unwrap().position;
+                let moved = distance_between(previous, next);
+                assert!((moved - step).abs() < 0.02, "moved {} at {}", moved, travelled);
+                previous = next;
+                travelled += step;
+            }
+        }
+    }
+
+    #[test]
+    fn turning_in_place_takes_time() {
+        let mut path = CameraPath::new();
+        path.keyframes = vec![key(3.0, 5.0, 3.0, 0.0), key(3.0, 5.0, 3.0, 90.0)];
+        let length = path.length();
+        assert!((length / path.speed - 90.0 / MAX_TURN_RATE).abs() < 1e-3);
+        assert!((path.sample(length / 2.0).unwrap().yaw - 45.0).abs() < 0.5);
+
+        // Zooming without moving or turning stretches the segment too
+        path.keyframes[1] = Keyframe { fov: 30.0, ..path.keyframes[0] };
+        assert!((path.length() / path.speed - 40.0 / MAX_ZOOM_RATE).abs() < 1e-3);
+    }
+
+    #[test]
+    fn yaw_turns_the_short_way_round() {
+        let path = CameraPath::from_text("key 0 5 0 350 0 70\nkey 10 5 0 10 0 70\nkey 20 5 0 -340 0 70").unwrap();
+        let yaws: Vec<f32> = path.keyframes.iter().map(|k| k.yaw).collect();
+        assert_eq!(yaws, vec![350.0, 370.0, 380.0]);
+        let middle = path.sample(path.keyframe_distances()[1] / 2.0).unwrap();
+        assert!((middle.yaw - 360.0).abs() < 2.0);
+    }
+
+    #[test]
+    fn sampling_clamps_to_the_ends() {
+        let path = zigzag(Interpolation::CatmullRom);
+        assert_eq!(path.sample(-5.0), Some(path.keyframes[0]));
+        let end = path.sample(path.length() + 5.0).unwrap();
+        assert!(distance_between(end.position, path.keyframes[3].position) < 1e-3);
+        assert_eq!(CameraPath::new().sample(1.0), None);
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let mut path = zigzag(Interpolation::Bezier);
+        path.speed = 2.5;
+        let loaded = CameraPath::from_text(&path.to_text()).unwrap();
+        assert_eq!(loaded.keyframes, path.keyframes);
+        assert_eq!(loaded.interpolation, Interpolation::Bezier);
+        assert_eq!(loaded.speed, 2.5);
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        assert_eq!(CameraPath::from_text("speed 3\nkey 1 2 3").err(), Some(PathError::BadLine(2)));
+        assert_eq!(CameraPath::from_text("zoom 3").err(), Some(PathError::BadLine(1)));
+        for speed in ["0", "-2", "NaN", "inf"] {
+            assert_eq!(CameraPath::from_text(&format!("speed {}", speed)).err(), Some(PathError::BadLine(1)));
+        }
+        assert_eq!(
+            CameraPath::from_text("interpolation linear").err(),
+            Some(PathError::UnknownInterpolation("linear".to_string()))
+        );
+    }
+
+    fn distance_between(a: (f32, f32, f32), b: (f32, f32, f32)) -> f32 {
+        ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt()
+    }
+}
@@ -0,0 +1,226 @@
+use glm::{vec3, Vec3};
+
+use crate::{Camera, World};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CameraView {
+    FirstPerson,
+    ThirdPerson, // Behind the head on a boom that shortens instead of passing through blocks
+    Orbit,       // Circles a pivot block, zoomed with the mouse wheel
+}
+
+impl CameraView {
+    pub fn next(self) -> CameraView {
+        match self {
+            CameraView::FirstPerson => CameraView::ThirdPerson,
+            CameraView::ThirdPerson => CameraView::Orbit,
+            CameraView::Orbit => CameraView::FirstPerson,
+        }
+    }
+}
+
+// Gap kept between the third-person camera and the block its boom ran into
+const BOOM_MARGIN: f32 = 0.2;
+// How quickly a shortened boom grows back once the way is clear (per second)
+const BOOM_RETURN_RATE: f32 = 6.0;
+// Sideways spread of the extra boom rays, so the near plane doesn't clip into walls
+const BOOM_PROBE: f32 = 0.15;
+pub const MIN_ORBIT_DISTANCE: f32 = 2.0;
+pub const MAX_ORBIT_DISTANCE: f32 = 80.0;
+
+// Places the rendered view relative to the first-person camera, which keeps doing the moving
+pub struct CameraRig {
+    pub view: CameraView,
+    pub boom_length: f32,     // Third-person distance behind the head when nothing is in the way
+    boom: f32,                // Current boom length after collisions
+    pub orbit_pivot: Vec3,
+    pub orbit_distance: f32,
+    pub turntable_speed: f32, // Automatic orbit rotation in degrees per second
+    orbit_yaw: f32,
+    orbit_pitch: f32,
+}
+
+impl CameraRig {
+    pub fn new() -> Self {
+        Self {
+            view: CameraView::FirstPerson,
+            boom_length: 4.0,
+            boom: 0.0,
+            orbit_pivot: vec3(0.0, 0.0, 0.0),
+            orbit_distance: 12.0,
+            turntable_speed: 0.0,
+            orbit_yaw: -90.0,
+            orbit_pitch: -30.0,
+        }
+    }
+
+    // Start orbiting `pivot` from where the camera is now, so the view doesn't jump
+    pub fn start_orbit(&mut self, camera: &Camera, pivot: Vec3) {
+        self.view = CameraView::Orbit;
+        self.orbit_pivot = pivot;
+        let offset = camera.position - pivot;
+        let distance = glm::length(offset);
+        self.orbit_distance = distance.clamp(MIN_ORBIT_DISTANCE, MAX_ORBIT_DISTANCE);
+        if distance > 0.0 {
+            let toward_pivot = -offset / distance;
+            self.orbit_pitch = toward_pivot.y.asin().to_degrees();
+            self.orbit_yaw = toward_pivot.z.atan2(toward_pivot.x).to_degrees();
+        } else {
+            self.orbit_yaw = camera.yaw;
+            self.orbit_pitch = camera.pitch;
+        }
+    }
+
+    pub fn orbit_mouse(&mut self, x_offset: f32, y_offset: f32, sensitivity: f32) {
+        self.orbit_yaw += x_offset * sensitivity;
+        self.orbit_pitch = (self.orbit_pitch + y_offset * sensitivity).clamp(-89.0, 89.0);
+    }
+
+    // Each wheel notch moves a tenth of the way in or out
+    pub fn zoom(&mut self, notches: i32) {
+        let distance = self.orbit_distance * 0.9f32.powi(notches);
+        self.orbit_distance = distance.clamp(MIN_ORBIT_DISTANCE, MAX_ORBIT_DISTANCE);
+    }
+
+    // Advance the turntable and the third-person boom by one frame
+    pub fn update(&mut self, camera: &Camera, world: &World, delta_time: f32) {
+        match self.view {
+            CameraView::FirstPerson => self.boom = 0.0,
+            CameraView::ThirdPerson => {
+                // Pull in at once when something is in the way, ease back out when it's gone
+                let clear = self.clear_boom_length(camera, world);
+                if clear < self.boom {
+                    self.boom = clear;
+                } else {
+                    let blend = 1.0 - (-BOOM_RETURN_RATE * delta_time).exp();
+                    self.boom += (clear - self.boom) * blend;
+                }
+            }
+            CameraView::Orbit => self.orbit_yaw += self.turntable_speed * delta_time,
+        }
+    }
+
+    // Longest boom up to `boom_length` that stays clear of solid blocks
+    fn clear_boom_length(&self, camera: &Camera, world: &World) -> f32 {
+        let back = -camera.front;
+        let probes = [
+            vec3(0.0, 0.0, 0.0),
+            camera.right * BOOM_PROBE,
+            camera.right * -BOOM_PROBE,
+            camera.up * BOOM_PROBE,
+            camera.up * -BOOM_PROBE,
+        ];
+        probes
+            .iter()
+            .map(|&offset| match world.raycast(camera.position + offset, back, self.boom_length, false) {
+                Some(hit) => (hit.distance - BOOM_MARGIN).max(0.0),
+                None => self.boom_length,
+            })
+            .fold(self.boom_length, f32::min)
+    }
+
+    // How far the rendered eye is from the first-person head, added to reach when picking
+    pub fn eye_offset(&self) -> f32 {
+        match self.view {
+            CameraView::FirstPerson => 0.0,
+            CameraView::ThirdPerson => self.boom,
+            CameraView::Orbit => self.orbit_distance,
+        }
+    }
+
+    // Camera the frame is rendered and picked from
+    pub fn view_camera(&self, camera: &Camera) -> Camera {
+        match self.view {
+            CameraView::FirstPerson => camera.clone(),
+            CameraView::ThirdPerson => camera.viewed_from(camera.position - camera.front * self.boom, camera.front),
+            CameraView::Orbit => {
+                let (yaw, pitch) = (self.orbit_yaw.to_radians(), self.orbit_pitch.to_radians());
+                let front = vec3(yaw.cos() * pitch.cos(), pitch.sin(), yaw.sin() * pitch.cos());
+                camera.viewed_from(self.orbit_pivot - front * self.orbit_distance, front)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VoxelType;
+
+    fn camera_at(position: Vec3) -> Camera {
+        let mut camera = Camera::new();
+        camera.position = position;
+        camera.yaw = 0.0;
+        camera.pitch = 0.0;
+        camera.process_mouse_movement(0.0, 0.0); // Look along +X
+        camera
+    }
+
+    #[test]
+    fn boom_stops_short_of_a_wall() {
+        let mut world = World::new();
+        for y in 0..4 {
+            for z in -2..=2 {
+                world.set_voxel(-3, y, z, VoxelType::Stone);
+            }
+        }
+        let camera = camera_at(vec3(0.5, 1.5, 0.5));
+        let mut rig = CameraRig::new();
+        rig.view = CameraView::ThirdPerson;
+        rig.update(&camera, &world, 0.1);
+
+        let eye = rig.view_camera(&camera).position;
+        assert!(eye.x > -2.0 + BOOM_MARGIN - 1e-3);
+        assert!(eye.x < 0.5);
+    }
+
+    #[test]
+    fn boom_eases_back_out_when_clear() {
+        let mut world = World::new();
+        world.set_voxel(-2, 1, 0, VoxelType::Stone);
+        let camera = camera_at(vec3(0.5, 1.5, 0.5));
+        let mut rig = CameraRig::new();
+        rig.view = CameraView::ThirdPerson;
+        rig.update(&camera, &world, 0.1);
+        let blocked = rig.eye_offset();
+        assert!(blocked < rig.boom_length);
+
+        world.set_voxel(-2, 1, 0, VoxelType::Air);
+        rig.update(&camera, &world, 0.05);
+        let easing = rig.eye_offset();
+        assert!(easing > blocked && easing < rig.boom_length);
+        for _ in 0..100 {
+            rig.update(&camera, &world, 0.05);
+        }
+        assert!((rig.eye_offset() - rig.boom_length).abs() < 1e-3);
+    }
+
+    #[test]
+    fn orbit_keeps_the_pivot_centered() {
+        let camera = camera_at(vec3(0.0, 8.0, 10.0));
+        let pivot = vec3(4.5, 2.5, -3.5);
+        let mut rig = CameraRig::new();
+        rig.start_orbit(&camera, pivot);
+        rig.turntable_speed = 45.0;
+
+        let start = rig.view_camera(&camera);
+        assert!(glm::length(start.position - camera.position) < 1e-3);
+        for _ in 0..10 {
+            rig.update(&camera, &World::new(), 0.1);
+            rig.orbit_mouse(5.0, -3.0, 1.0);
+            let view = rig.view_camera(&camera);
+            let to_pivot = pivot - view.position;
+            assert!((glm::length(to_pivot) - rig.orbit_distance).abs() < 1e-3);
+            assert!(glm::length(glm::normalize(to_pivot) - view.front) < 1e-4);
+        }
+    }
+
+    #[test]
+    fn zoom_is_clamped() {
+        let mut rig = CameraRig::new();
+        rig.zoom(100);
+        assert_eq!(rig.orbit_distance, MIN_ORBIT_DISTANCE);
+        rig.zoom(-100);
+        assert_eq!(rig.orbit_distance, MAX_ORBIT_DISTANCE);
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::terrain::{self, TerrainSettings};
+use crate::Chunk;
+
+// A chunk waiting to be generated
+struct Job {
+    position: (i32, i32, i32),
+    settings: Arc<TerrainSettings>,
+    cancelled: Arc<AtomicBool>,
+}
+
+// A generated chunk on its way back to the main thread
+struct Finished {
+    chunk: Chunk,
+    cancelled: Arc<AtomicBool>,
+}
+
+// State shared between the main thread and the workers
+struct Queue {
+    jobs: Vec<Job>,
+    center: (i32, i32, i32), // Chunk the camera is in, used to prioritise jobs
+    shutdown: bool,
+}
+
+// Pool of background threads generating chunks, nearest to the camera first
+pub struct ChunkWorkers {
+    shared: Arc<(Mutex<Queue>, Condvar)>,
+    results: Receiver<Finished>,
+    pending: HashMap<(i32, i32, i32), Arc<AtomicBool>>, // Requested but not yet integrated
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ChunkWorkers {
+    pub fn new(thread_count: usize) -> Self {
+        let shared = Arc::new((
+            Mutex::new(Queue { jobs: Vec::new(), center: (0, 0, 0), shutdown: false }),
+            Condvar::new(),
+        ));
+        let (sender, results) = channel();
+
+        let workers = (0..thread_count.max(1))
+            .map(|i| {
+                let shared = shared.clone();
+                let sender = sender.clone();
+                thread::Builder::new()
+                    .name(format!("chunk-worker-{}", i))
+                    .spawn(move || worker_loop(shared, sender))
+                    .expect("Failed to spawn chunk worker")
+            })
+            .collect();
+
+        Self { shared, results, pending: HashMap::new(), workers }
+    }
+
+    // Use all but one core, leaving the main thread free to render
+    pub fn default_thread_count() -> usize {
+        thread::available_parallelism()
+            .map(|n| n.get().saturating_sub(1).max(1))
+            .unwrap_or(2)
+    }
+
+    pub fn is_pending(&self, position: (i32, i32, i32)) -> bool {
+        self.pending.contains_key(&position)
+    }
+
+    // Queue a chunk for generation unless it is already on its way
+    pub fn request(&mut self, position: (i32, i32, i32), settings: &Arc<TerrainSettings>) {
+        if self.pending.contains_key(&position) {
+            return;
+        }
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.pending.insert(position, cancelled.clone());
+
+        let (lock, condvar) = &*self.shared;
+        lock.lock().unwrap().jobs.push(Job { position, settings: settings.clone(), cancelled });
+        condvar.notify_one();
+    }
+
+    // Update the chunk jobs are prioritised around
+    pub fn set_center(&self, center: (i32, i32, i32)) {
+        let (lock, _) = &*self.shared;
+        lock.lock().unwrap().center = center;
+    }
+
+    // Cancel every pending chunk further than `radius` chunks from `center`
+    pub fn cancel_outside(&mut self, center: (i32, i32, i32), radius: i32) {
+        self.pending.retain(|&position, cancelled| {
+            let keep = chebyshev_distance(position, center) <= radius;
+            if !keep {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+            keep
+        });
+        self.drop_cancelled_jobs();
+    }
+
+    // Cancel everything, e.g. before regenerating the world with new settings
+    pub fn cancel_all(&mut self) {
+        for cancelled in self.pending.values() {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+        self.pending.clear();
+        self.drop_cancelled_jobs();
+    }
+
+    // Collect up to `budget` finished chunks so integration never stalls a frame
+    pub fn poll(&mut self, budget: usize) -> Vec<Chunk> {
+        let mut chunks = Vec::new();
+        while chunks.len() < budget {
+            let finished = match self.results.try_recv() {
+                Ok(finished) => finished,
+                Err(_) => break,
+            };
+
+            // Ignore results of cancelled or superseded requests
+            let position = finished.chunk.position;
+            let current = match self.pending.get(&position) {
+                Some(cancelled) => Arc::ptr_eq(cancelled, &finished.cancelled),
+                None => false,
+            };
+            if current && !finished.cancelled.load(Ordering::Relaxed) {
+                self.pending.remove(&position);
+                chunks.push(finished.chunk);
+            }
+        }
+        chunks
+    }
+
+    fn drop_cancelled_jobs(&self) {
+        let (lock, _) = &*self.shared;
+        lock.lock().unwrap().jobs.retain(|job| !job.cancelled.load(Ordering::Relaxed));
+    }
+}
+
+impl Drop for ChunkWorkers {
+    fn drop(&mut self) {
+        {
+            let (lock, condvar) = &*self.shared;
+            let mut queue = lock.lock().unwrap();
+            queue.shutdown = true;
+            queue.jobs.clear();
+            condvar.notify_all();
+        }
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn worker_loop(shared: Arc<(Mutex<Queue>, Condvar)>, sender: Sender<Finished>) {
+    let (lock, condvar) = &*shared;
+    loop {
+        let job = {
+            let mut queue = lock.lock().unwrap();
+            loop {
+                if queue.shutdown {
+                    return;
+                }
+                queue.jobs.retain(|job| !job.cancelled.load(Ordering::Relaxed));
+                if !queue.jobs.is_empty() {
+                    break;
+                }
+                queue = condvar.wait(queue).unwrap();
+            }
+
+            let nearest = nearest_job(&queue.jobs, queue.center).unwrap();
+            queue.jobs.swap_remove(nearest)
+        };
+
+        let chunk = terrain::generate_chunk(&job.settings, job.position);
+        if job.cancelled.load(Ordering::Relaxed) {
+            continue;
+        }
+        if sender.send(Finished { chunk, cancelled: job.cancelled }).is_err() {
+            return;
+        }
+    }
+}
+
+// Index of the job closest to the camera
+fn nearest_job(jobs: &[Job], center: (i32, i32, i32)) -> Option<usize> {
+    (0..jobs.len()).min_by_key(|&i| squared_distance(jobs[i].position, center))
+}
+
+fn squared_distance(a: (i32, i32, i32), b: (i32, i32, i32)) -> i32 {
+    let (dx, dy, dz) = (a.0 - b.0, a.1 - b.1, a.2 - b.2);
+    dx * dx + dy * dy + dz * dz
+}
+
+// Distance in chunks along the furthest axis, matching the cube of loaded chunks
+pub fn chebyshev_distance(a: (i32, i32, i32), b: (i32, i32, i32)) -> i32 {
+    (a.0 - b.0).abs().max((a.1 - b.1).abs()).max((a.2 - b.2).abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn job(position: (i32, i32, i32)) -> Job {
+        Job {
+            position,
+            settings: Arc::new(TerrainSettings::new()),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    // Poll until nothing is pending, giving up after a few seconds
+    fn drain(workers: &mut ChunkWorkers) -> Vec<(i32, i32, i32)> {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut positions = Vec::new();
+        while !workers.pending.is_empty() && Instant::now() < deadline {
+            positions.extend(workers.poll(usize::MAX).into_iter().map(|chunk| chunk.position));
+            thread::sleep(Duration::from_millis(1));
+        }
+        positions
+    }
+
+    #[test]
+    fn nearest_job_goes_first() {
+        let jobs = vec![job((5, 0, 0)), job((-1, 0, 1)), job((0, 3, 0)), job((2, 2, 2))];
+        assert_eq!(nearest_job(&jobs, (0, 0, 0)), Some(1));
+        assert_eq!(nearest_job(&jobs, (4, 0, 0)), Some(0));
+        assert_eq!(nearest_job(&[], (0, 0, 0)), None);
+    }
+
+    #[test]
+    fn cancelled_chunks_are_never_delivered() {
+        let settings = Arc::new(TerrainSettings::new());
+        let mut workers = ChunkWorkers::new(2);
+        for x in -3..=3 {
+            workers.request((x, 0, 0), &settings);
+        }
+        workers.cancel_outside((0, 0, 0), 1);
+        assert!(!workers.is_pending((3, 0, 0)) && workers.is_pending((1, 0, 0)));
+
+        let mut delivered = drain(&mut workers);
+        delivered.sort();
+        assert_eq!(delivered, vec![(-1, 0, 0), (0, 0, 0), (1, 0, 0)]);
+    }
+
+    #[test]
+    fn cancel_all_drops_everything() {
+        let settings = Arc::new(TerrainSettings::new());
+        let mut workers = ChunkWorkers::new(1);
+        for z in 0..4 {
+            workers.request((0, 0, z), &settings);
+        }
+        workers.cancel_all();
+        thread::sleep(Duration::from_millis(50));
+        assert!(workers.poll(usize::MAX).is_empty());
+        assert!(!workers.is_pending((0, 0, 0)));
+    }
+}
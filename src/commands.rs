@@ -0,0 +1,78 @@
+use std::fmt;
+
+use crate::GameMode;
+
+// Something typed into the command line
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Command {
+    GameMode(GameMode),
+    Help,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum CommandError {
+    Empty,
+    UnknownCommand(String),
+    Usage(&'static str), // Right command, wrong arguments; holds the expected form
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandError::Empty => write!(f, "Type a command, such as /help"),
+            CommandError::UnknownCommand(name) => write!(f, "Unknown command \"{}\" (try /help)", name),
+            CommandError::Usage(usage) => write!(f, "Usage: {}", usage),
+        }
+    }
+}
+
+pub const HELP: &str = "Commands: /gamemode <creative|survival|spectator>, /help";
+
+// Parse a command, with or without its leading slash. Game modes can be shortened to
+// any unambiguous prefix, like "/gamemode c".
+pub fn parse(text: &str) -> Result<Command, CommandError> {
+    let text = text.trim();
+    let text = text.strip_prefix('/').unwrap_or(text);
+    let mut words = text.split_whitespace();
+    let name = words.next().ok_or(CommandError::Empty)?.to_lowercase();
+    let arguments: Vec<String> = words.map(|w| w.to_lowercase()).collect();
+    match name.as_str() {
+        "gamemode" | "gm" => {
+            const USAGE: &str = "/gamemode <creative|survival|spectator>";
+            let mode = match arguments.as_slice() {
+                [mode] => mode,
+                _ => return Err(CommandError::Usage(USAGE)),
+            };
+            let matches: Vec<GameMode> = GameMode::ALL.iter().copied().filter(|m| m.name().starts_with(mode.as_str())).collect();
+            match matches.as_slice() {
+                [mode] => Ok(Command::GameMode(*mode)),
+                _ => Err(CommandError::Usage(USAGE)),
+            }
+        }
+        "help" if arguments.is_empty() => Ok(Command::Help),
+        "help" => Err(CommandError::Usage("/help")),
+        _ => Err(CommandError::UnknownCommand(name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_game_mode_names_and_prefixes() {
+        assert_eq!(parse("/gamemode survival"), Ok(Command::GameMode(GameMode::Survival)));
+        assert_eq!(parse("gm SPEC"), Ok(Command::GameMode(GameMode::Spectator)));
+        assert_eq!(parse(" /gamemode c "), Ok(Command::GameMode(GameMode::Creative)));
+        assert_eq!(parse("/help"), Ok(Command::Help));
+    }
+
+    #[test]
+    fn rejects_unknown_and_ambiguous_input() {
+        assert_eq!(parse("/"), Err(CommandError::Empty));
+        assert_eq!(parse("/fly"), Err(CommandError::UnknownCommand("fly".to_string())));
+        // "s" could be survival or spectator
+        assert!(matches!(parse("/gamemode s"), Err(CommandError::Usage(_))));
+        assert!(matches!(parse("/gamemode"), Err(CommandError::Usage(_))));
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+
+use crate::World;
+
+// Limits that keep a fill from spreading across the whole world
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct FillSettings {
+    pub max_volume: usize,
+    pub use_bounds: bool,
+    pub bounds_min: (i32, i32, i32),
+    pub bounds_max: (i32, i32, i32),
+}
+
+impl FillSettings {
+    pub fn new() -> Self {
+        Self {
+            max_volume: 4096,
+            use_bounds: false,
+            bounds_min: (-16, 0, -16),
+            bounds_max: (31, 15, 31),
+        }
+    }
+
+    fn in_bounds(&self, cell: (i32, i32, i32)) -> bool {
+        if !self.use_bounds {
+            return true;
+        }
+        let (min, max) = (self.bounds_min, self.bounds_max);
+        cell.0 >= min.0.min(max.0) && cell.0 <= min.0.max(max.0)
+            && cell.1 >= min.1.min(max.1) && cell.1 <= min.1.max(max.1)
+            && cell.2 >= min.2.min(max.2) && cell.2 <= min.2.max(max.2)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FillError {
+    TooLarge(usize), // The region has more cells than the volume cap
+    OutsideBounds,
+}
+
+impl fmt::Display for FillError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FillError::TooLarge(limit) => write!(f, "Fill region is larger than {} blocks", limit),
+            FillError::OutsideBounds => write!(f, "Fill must start inside the selection"),
+        }
+    }
+}
+
+// Every cell face-connected to `start` with the same voxel type. Nothing is returned if the
+// region is bigger than the cap, so a fill either completes or doesn't happen at all.
+pub fn flood_fill_cells(world: &World, start: (i32, i32, i32), settings: &FillSettings) -> Result<Vec<(i32, i32, i32)>, FillError> {
+    if !settings.in_bounds(start) {
+        return Err(FillError::OutsideBounds);
+    }
+    let voxel_type = world.get_voxel(start.0, start.1, start.2);
+
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+    let mut cells = Vec::new();
+    visited.insert(start);
+    queue.push_back(start);
+
+    while let Some((x, y, z)) = queue.pop_front() {
+        cells.push((x, y, z));
+        if cells.len() > settings.max_volume {
+            return Err(FillError::TooLarge(settings.max_volume));
+        }
+
+        let neighbours = [
+            (x + 1, y, z), (x - 1, y, z),
+            (x, y + 1, z), (x, y - 1, z),
+            (x, y, z + 1), (x, y, z - 1),
+        ];
+        for next in neighbours {
+            if settings.in_bounds(next)
+                && !visited.contains(&next)
+                && world.get_voxel(next.0, next.1, next.2) == voxel_type
+            {
+                visited.insert(next);
+                queue.push_back(next);
+            }
+        }
+    }
+    Ok(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VoxelType;
+
+    #[test]
+    fn stops_at_differing_voxels() {
+        let mut world = World::new();
+        for x in 0..4 {
+            for z in 0..3 {
+                world.set_voxel(x, 2, z, VoxelType::Stone);
+            }
+        }
+        world.set_voxel(3, 2, 1, VoxelType::Dirt);
+        world.set_voxel(6, 2, 0, VoxelType::Stone); // Not connected to the rest
+        let cells = flood_fill_cells(&world, (0, 2, 0), &FillSettings::new()).unwrap();
+        assert_eq!(cells.len(), 11);
+        assert!(!cells.contains(&(3, 2, 1)) && !cells.contains(&(6, 2, 0)));
+        assert!(cells.iter().all(|&(x, y, z)| world.get_voxel(x, y, z) == VoxelType::Stone));
+    }
+
+    #[test]
+    fn gives_up_past_the_volume_cap() {
+        let world = World::new();
+        let settings = FillSettings { max_volume: 100, ..FillSettings::new() };
+        assert_eq!(flood_fill_cells(&world, (0, 5, 0), &settings), Err(FillError::TooLarge(100)));
+    }
+
+    #[test]
+    fn bounds_contain_the_fill() {
+        let world = World::new();
+        let settings = FillSettings {
+            use_bounds: true,
+            bounds_min: (2, 3, 4),
+            bounds_max: (0, 1, 0), // Corners may be given in any order
+            ..FillSettings::new()
+        };
+        let cells = flood_fill_cells(&world, (1, 2, 3), &settings).unwrap();
+        assert_eq!(cells.len(), 3 * 3 * 5);
+        assert_eq!(flood_fill_cells(&world, (3, 2, 3), &settings), Err(FillError::OutsideBounds));
+    }
+}
@@ -0,0 +1,62 @@
+use std::time::Instant;
+
+// Length of one simulation tick in seconds
+pub const FIXED_TIMESTEP: f32 = 1.0 / 60.0;
+// Longest frame the simulation catches up on, so a stall doesn't trigger hundreds of ticks
+const MAX_FRAME_DELTA: f32 = 0.25;
+
+// Measures real frame times and hands out fixed simulation ticks from an accumulator
+pub struct FrameClock {
+    start: Instant,
+    last_frame: Instant,
+    delta: f32,
+    accumulator: f32,
+    ticks_this_frame: u32,
+}
+
+impl FrameClock {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            start: now,
+            last_frame: now,
+            delta: 0.0,
+            accumulator: 0.0,
+            ticks_this_frame: 0,
+        }
+    }
+
+    // Start a new frame, returning the seconds since the previous one
+    pub fn begin_frame(&mut self) -> f32 {
+        let now = Instant::now();
+        self.delta = now.duration_since(self.last_frame).as_secs_f32();
+        self.last_frame = now;
+        self.accumulator += self.delta.min(MAX_FRAME_DELTA);
+        self.ticks_this_frame = 0;
+        self.delta
+    }
+
+    // Consume one simulation tick if enough time has built up; call in a loop until false
+    pub fn step(&mut self) -> bool {
+        if self.accumulator < FIXED_TIMESTEP {
+            return false;
+        }
+        self.accumulator -= FIXED_TIMESTEP;
+        self.ticks_this_frame += 1;
+        true
+    }
+
+    // Seconds since the previous frame
+    pub fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    // Seconds since the clock was created
+    pub fn elapsed(&self) -> f32 {
+        self.start.elapsed().as_secs_f32()
+    }
+
+    pub fn ticks_this_frame(&self) -> u32 {
+        self.ticks_this_frame
+    }
+}
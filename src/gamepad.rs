@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+
+use sdl2::controller::{Axis, Button, GameController};
+use sdl2::event::Event;
+use sdl2::GameControllerSubsystem;
+
+use crate::input_map::Action;
+
+// Trigger travel (0..1) that counts as a press, and the lower point it must drop back under
+// to release, so a trigger resting near the threshold doesn't chatter
+const TRIGGER_PRESS: f32 = 0.5;
+const TRIGGER_RELEASE: f32 = 0.3;
+
+// Stick tuning shown in the settings window
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GamepadSettings {
+    pub deadzone: f32,      // Fraction of stick travel ignored around the center
+    pub look_curve: f32,    // Exponent on look input; above 1 gives finer aim near the center
+    pub look_speed: f32,    // Turn rate at full deflection in degrees per second
+    pub invert_look_y: bool,
+}
+
+impl GamepadSettings {
+    pub fn new() -> Self {
+        Self {
+            deadzone: 0.15,
+            look_curve: 2.0,
+            look_speed: 180.0,
+            invert_look_y: false,
+        }
+    }
+}
+
+// Discrete things a controller asks for; held buttons and sticks are read each frame instead
+#[derive(Clone, PartialEq, Debug)]
+pub enum GamepadAction {
+    Connected(String),
+    Disconnected(String),
+    Press(Action),
+    Release(Action),
+    PreviousSlot,
+    NextSlot,
+    Menu(egui::Key, bool), // Key for navigating the egui panels, and whether shift is held
+}
+
+// Stick position after a radial deadzone, rescaled so movement starts from zero at its edge,
+// then shaped by `curve`
+pub fn shape_stick(x: f32, y: f32, deadzone: f32, curve: f32) -> (f32, f32) {
+    let magnitude = (x * x + y * y).sqrt();
+    if magnitude <= deadzone {
+        return (0.0, 0.0);
+    }
+    let scaled = ((magnitude - deadzone) / (1.0 - deadzone)).min(1.0).powf(curve);
+    (x / magnitude * scaled, y / magnitude * scaled)
+}
+
+fn axis_value(raw: i16) -> f32 {
+    (raw as f32 / 32767.0).max(-1.0)
+}
+
+// Which triggers each controller has pulled past the press point, by joystick instance id
+#[derive(Default)]
+struct Triggers {
+    held: HashMap<u32, [bool; 2]>, // Left and right trigger
+}
+
+impl Triggers {
+    // Press or release the trigger's action as it crosses the thresholds
+    fn update(&mut self, which: u32, axis: Axis, raw: i16) -> Option<GamepadAction> {
+        let (side, action) = match axis {
+            Axis::TriggerLeft => (0, Action::Place),
+            Axis::TriggerRight => (1, Action::Break),
+            _ => return None,
+        };
+        let held = &mut self.held.entry(which).or_insert([false; 2])[side];
+        let value = axis_value(raw);
+        if !*held && value > TRIGGER_PRESS {
+            *held = true;
+            Some(GamepadAction::Press(action))
+        } else if *held && value < TRIGGER_RELEASE {
+            *held = false;
+            Some(GamepadAction::Release(action))
+        } else {
+            None
+        }
+    }
+
+    // Forget a controller, releasing whatever it still held so breaking or painting stops
+    fn remove(&mut self, which: u32) -> Vec<GamepadAction> {
+        let held = self.held.remove(&which).unwrap_or([false; 2]);
+        [Action::Place, Action::Break]
+            .iter()
+            .zip(held.iter())
+            .filter(|(_, &held)| held)
+            .map(|(&action, _)| GamepadAction::Release(action))
+            .collect()
+    }
+}
+
+// Every connected controller, opened and closed as they're plugged in and out
+pub struct Gamepads {
+    subsystem: GameControllerSubsystem,
+    controllers: HashMap<u32, GameController>, // By joystick instance id
+    triggers: Triggers,
+    pub settings: GamepadSettings,
+}
+
+impl Gamepads {
+    pub fn new(subsystem: GameControllerSubsystem) -> Self {
+        Self {
+            subsystem,
+            controllers: HashMap::new(),
+            triggers: Triggers::default(),
+            settings: GamepadSettings::new(),
+        }
+    }
+
+    pub fn connected(&self) -> usize {
+        self.controllers.len()
+    }
+
+    // SDL reports controllers that are already plugged in as added at startup, so
+    // connecting and hot-plugging take the same path
+    pub fn handle_event(&mut self, event: &Event) -> Vec<GamepadAction> {
+        match *event {
+            Event::ControllerDeviceAdded { which, .. } => match self.subsystem.open(which) {
+                Ok(controller) => {
+                    let name = controller.name();
+                    self.controllers.insert(controller.instance_id(), controller);
+                    vec![GamepadAction::Connected(name)]
+                }
+                Err(_) => Vec::new(),
+            },
+            Event::ControllerDeviceRemoved { which, .. } => {
+                let mut actions = self.triggers.remove(which);
+                if let Some(controller) = self.controllers.remove(&which) {
+                    actions.push(GamepadAction::Disconnected(controller.name()));
+                }
+                actions
+            }
+            Event::ControllerAxisMotion { which, axis, value, .. } => self.triggers.update(which, axis, value).into_iter().collect(),
+            Event::ControllerButtonDown { button, .. } => {
+                let action = match button {
+                    Button::LeftShoulder => Some(GamepadAction::PreviousSlot),
+                    Button::RightShoulder => Some(GamepadAction::NextSlot),
+                    Button::Start => Some(GamepadAction::Press(Action::ToggleCapture)),
+                    Button::Y => Some(GamepadAction::Press(Action::ToggleFly)),
+                    Button::X => Some(GamepadAction::Press(Action::PickBlock)),
+                    _ => menu_key(button).map(|(key, shift)| GamepadAction::Menu(key, shift)),
+                };
+                action.into_iter().collect()
+            }
+            Event::ControllerButtonUp { button: Button::X, .. } => vec![GamepadAction::Release(Action::PickBlock)],
+            _ => Vec::new(),
+        }
+    }
+
+    fn held(&self, button: Button) -> bool {
+        self.controllers.values().any(|c| c.button(button))
+    }
+
+    // Shaped stick with the largest deflection across all controllers
+    fn stick(&self, x: Axis, y: Axis, curve: f32) -> (f32, f32) {
+        self.controllers
+            .values()
+            .map(|c| shape_stick(axis_value(c.axis(x)), axis_value(c.axis(y)), self.settings.deadzone, curve))
+            .fold((0.0, 0.0), |best, s| if s.0.abs() + s.1.abs() > best.0.abs() + best.1.abs() { s } else { best })
+    }
+
+    // Forward, right and up movement from the left stick, A (up) and B (down)
+    pub fn movement(&self) -> (f32, f32, f32) {
+        let (x, y) = self.stick(Axis::LeftX, Axis::LeftY, 1.0);
+        let up = self.held(Button::A) as i32 - self.held(Button::B) as i32;
+        (-y, x, up as f32) // Stick Y points down
+    }
+
+    // Whether the sticks are clicked in to sprint (left) or crouch (right)
+    pub fn stance(&self) -> (bool, bool) {
+        (self.held(Button::LeftStick), self.held(Button::RightStick))
+    }
+
+    // Yaw and pitch change in degrees from the right stick over `delta_time` seconds
+    pub fn look(&self, delta_time: f32) -> (f32, f32) {
+        let (x, y) = self.stick(Axis::RightX, Axis::RightY, self.settings.look_curve);
+        let pitch = if self.settings.invert_look_y { y } else { -y };
+        let speed = self.settings.look_speed * delta_time;
+        (x * speed, pitch * speed)
+    }
+}
+
+// D-pad moves keyboard focus between widgets and left/right adjusts sliders; A activates
+// the focused widget and B lets go of it
+fn menu_key(button: Button) -> Option<(egui::Key, bool)> {
+    match button {
+        Button::DPadDown => Some((egui::Key::Tab, false)),
+        Button::DPadUp => Some((egui::Key::Tab, true)),
+        Button::DPadLeft => Some((egui::Key::ArrowLeft, false)),
+        Button::DPadRight => Some((egui::Key::ArrowRight, false)),
+        Button::A => Some((egui::Key::Enter, false)),
+        Button::B => Some((egui::Key::Escape, false)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadzone_swallows_small_deflections() {
+        assert_eq!(shape_stick(0.1, -0.05, 0.15, 1.0), (0.0, 0.0));
+        let (x, y) = shape_stick(0.2, 0.0, 0.15, 1.0);
+        assert!(x > 0.0 && x < 0.1 && y == 0.0);
+    }
+
+    #[test]
+    fn full_deflection_reaches_one() {
+        let (x, y) = shape_stick(0.0, -1.0, 0.15, 2.0);
+        assert!(x.abs() < 1e-6 && (y + 1.0).abs() < 1e-6);
+        let (x, y) = shape_stick(1.0, 1.0, 0.15, 2.0); // Square gate corner
+        assert!(((x * x + y * y).sqrt() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn triggers_press_and_release_with_hysteresis() {
+        let mut triggers = Triggers::default();
+        assert_eq!(triggers.update(0, Axis::TriggerRight, 20000), Some(GamepadAction::Press(Action::Break)));
+        assert_eq!(triggers.update(0, Axis::TriggerRight, 12000), None);
+        assert_eq!(triggers.update(0, Axis::TriggerRight, 5000), Some(GamepadAction::Release(Action::Break)));
+        assert_eq!(triggers.update(0, Axis::LeftX, 30000), None);
+    }
+
+    #[test]
+    fn unplugging_releases_held_triggers() {
+        let mut triggers = Triggers::default();
+        triggers.update(3, Axis::TriggerRight, 30000);
+        triggers.update(3, Axis::TriggerLeft, 30000);
+        triggers.update(3, Axis::TriggerLeft, 0);
+        triggers.update(4, Axis::TriggerLeft, 30000); // Another controller keeps its trigger
+        assert_eq!(triggers.remove(3), vec![GamepadAction::Release(Action::Break)]);
+        assert!(triggers.remove(3).is_empty());
+        assert_eq!(triggers.remove(4), vec![GamepadAction::Release(Action::Place)]);
+    }
+
+    #[test]
+    fn curve_softens_the_middle_of_the_range() {
+        let (linear, _) = shape_stick(0.5, 0.0, 0.0, 1.0);
+        let (curved, _) = shape_stick(0.5, 0.0, 0.0, 2.0);
+        assert!((linear - 0.5).abs() < 1e-6);
+        assert!((curved - 0.25).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,534 @@
+use std::fmt;
+
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::MouseButton;
+
+use crate::window_manager::window_manager::windows::HOTBAR_SLOTS;
+
+// Where the bindings are saved, next to the executable's working directory
+pub const CONTROLS_FILE: &str = "controls.txt";
+
+// Everything the player can bind keys or mouse buttons to
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    Jump,    // Also rises while flying
+    Descend, // Sinks while flying
+    Sprint,
+    Crouch,  // Also keeps the player from walking off edges
+    Break,
+    Place,
+    PickBlock,
+    Undo,
+    Redo,
+    BuildShape,
+    ToggleFly,
+    CycleView,
+    OrbitPivot,
+    AddKeyframe,
+    ToggleHud,
+    ToggleCapture,
+    OpenCommand,
+    HotbarSlot(usize),
+}
+
+impl Action {
+    pub fn all() -> Vec<Action> {
+        let mut actions = vec![
+            Action::MoveForward,
+            Action::MoveBackward,
+            Action::MoveLeft,
+            Action::MoveRight,
+            Action::Jump,
+            Action::Descend,
+            Action::Sprint,
+            Action::Crouch,
+            Action::Break,
+            Action::Place,
+            Action::PickBlock,
+            Action::Undo,
+            Action::Redo,
+            Action::BuildShape,
+            Action::ToggleFly,
+            Action::CycleView,
+            Action::OrbitPivot,
+            Action::AddKeyframe,
+            Action::ToggleHud,
+            Action::ToggleCapture,
+            Action::OpenCommand,
+        ];
+        actions.extend((0..HOTBAR_SLOTS).map(Action::HotbarSlot));
+        actions
+    }
+
+    // Name shown in the controls window and the help text
+    pub fn label(self) -> String {
+        let label = match self {
+            Action::MoveForward => "Move Forward",
+            Action::MoveBackward => "Move Backward",
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::Jump => "Jump / Fly Up",
+            Action::Descend => "Fly Down",
+            Action::Sprint => "Sprint",
+            Action::Crouch => "Crouch / Sneak",
+            Action::Break => "Break Block (hold in Survival)",
+            Action::Place => "Place Block",
+            Action::PickBlock => "Pick Block",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::BuildShape => "Build Shape",
+            Action::ToggleFly => "Toggle Walk/Fly",
+            Action::CycleView => "Cycle First Person/Third Person/Orbit",
+            Action::OrbitPivot => "Orbit Around Targeted Block",
+            Action::AddKeyframe => "Add Camera Path Keyframe",
+            Action::ToggleHud => "Toggle HUD",
+            Action::ToggleCapture => "Toggle Mouse Capture",
+            Action::OpenCommand => "Open Command Line",
+            Action::HotbarSlot(slot) => return format!("Hotbar Slot {}", slot + 1),
+        };
+        label.to_string()
+    }
+
+    // Name used in the controls file
+    fn id(self) -> String {
+        let id = match self {
+            Action::MoveForward => "move_forward",
+            Action::MoveBackward => "move_backward",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::Jump => "jump",
+            Action::Descend => "descend",
+            Action::Sprint => "sprint",
+            Action::Crouch => "crouch",
+            Action::Break => "break",
+            Action::Place => "place",
+            Action::PickBlock => "pick_block",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::BuildShape => "build_shape",
+            Action::ToggleFly => "toggle_fly",
+            Action::CycleView => "cycle_view",
+            Action::OrbitPivot => "orbit_pivot",
+            Action::AddKeyframe => "add_keyframe",
+            Action::ToggleHud => "toggle_hud",
+            Action::ToggleCapture => "toggle_capture",
+            Action::OpenCommand => "open_command",
+            Action::HotbarSlot(slot) => return format!("hotbar_{}", slot + 1),
+        };
+        id.to_string()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Input {
+    Key(Keycode),
+    Mouse(MouseButton),
+}
+
+impl Input {
+    pub fn is_modifier(self) -> bool {
+        matches!(
+            self,
+            Input::Key(Keycode::LCtrl | Keycode::RCtrl | Keycode::LShift | Keycode::RShift | Keycode::LAlt | Keycode::RAlt)
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct ModifierKeys {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl ModifierKeys {
+    pub fn from_mod(keymod: Mod) -> Self {
+        Self {
+            ctrl: keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD),
+            shift: keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+            alt: keymod.intersects(Mod::LALTMOD | Mod::RALTMOD),
+        }
+    }
+
+    pub fn from_keys(keys: &[Keycode]) -> Self {
+        let held = |a: Keycode, b: Keycode| keys.contains(&a) || keys.contains(&b);
+        Self {
+            ctrl: held(Keycode::LCtrl, Keycode::RCtrl),
+            shift: held(Keycode::LShift, Keycode::RShift),
+            alt: held(Keycode::LAlt, Keycode::RAlt),
+        }
+    }
+
+    // A modifier key doesn't count as modifying itself, so e.g. Left Ctrl can be bound alone
+    fn without_input(mut self, input: Input) -> Self {
+        match input {
+            Input::Key(Keycode::LCtrl) | Input::Key(Keycode::RCtrl) => self.ctrl = false,
+            Input::Key(Keycode::LShift) | Input::Key(Keycode::RShift) => self.shift = false,
+            Input::Key(Keycode::LAlt) | Input::Key(Keycode::RAlt) => self.alt = false,
+            _ => {}
+        }
+        self
+    }
+}
+
+// A key or mouse button, plus the modifiers that must be held with it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Binding {
+    pub input: Input,
+    pub modifiers: ModifierKeys,
+}
+
+impl Binding {
+    pub fn new(input: Input, modifiers: ModifierKeys) -> Self {
+        Self { input, modifiers: modifiers.without_input(input) }
+    }
+
+    fn key(key: Keycode) -> Self {
+        Self::new(Input::Key(key), ModifierKeys::default())
+    }
+
+    fn ctrl(key: Keycode) -> Self {
+        Self::new(Input::Key(key), ModifierKeys { ctrl: true, ..Default::default() })
+    }
+
+    fn mouse(button: MouseButton) -> Self {
+        Self::new(Input::Mouse(button), ModifierKeys::default())
+    }
+
+    // Parse the text written by `Display`, such as "Ctrl+Z" or "Mouse Left"
+    pub fn parse(text: &str) -> Option<Binding> {
+        let mut modifiers = ModifierKeys::default();
+        let mut rest = text.trim();
+        loop {
+            if let Some(r) = rest.strip_prefix("Ctrl+") {
+                modifiers.ctrl = true;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("Shift+") {
+                modifiers.shift = true;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("Alt+") {
+                modifiers.alt = true;
+                rest = r;
+            } else {
+                break;
+            }
+        }
+        let input = match rest {
+            "Mouse Left" => Input::Mouse(MouseButton::Left),
+            "Mouse Right" => Input::Mouse(MouseButton::Right),
+            "Mouse Middle" => Input::Mouse(MouseButton::Middle),
+            "Mouse X1" => Input::Mouse(MouseButton::X1),
+            "Mouse X2" => Input::Mouse(MouseButton::X2),
+            name => Input::Key(Keycode::from_name(name)?),
+        };
+        Some(Binding::new(input, modifiers))
+    }
+
+    // Whether this binding is held, given everything currently held down
+    fn is_held(&self, keys: &[Keycode], mouse: &[MouseButton]) -> bool {
+        let held = ModifierKeys::from_keys(keys);
+        let input_held = match self.input {
+            Input::Key(key) => keys.contains(&key),
+            Input::Mouse(button) => mouse.contains(&button),
+        };
+        input_held
+            && (!self.modifiers.ctrl || held.ctrl)
+            && (!self.modifiers.shift || held.shift)
+            && (!self.modifiers.alt || held.alt)
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.modifiers.alt {
+            write!(f, "Alt+")?;
+        }
+        match self.input {
+            Input::Key(key) => write!(f, "{}", key.name()),
+            Input::Mouse(MouseButton::Left) => write!(f, "Mouse Left"),
+            Input::Mouse(MouseButton::Right) => write!(f, "Mouse Right"),
+            Input::Mouse(MouseButton::Middle) => write!(f, "Mouse Middle"),
+            Input::Mouse(MouseButton::X1) => write!(f, "Mouse X1"),
+            Input::Mouse(MouseButton::X2) => write!(f, "Mouse X2"),
+            Input::Mouse(MouseButton::Unknown) => write!(f, "Mouse ?"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum BindingsError {
+    UnknownAction(String),
+    BadBinding(usize), // 1-based line number
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BindingsError::UnknownAction(name) => write!(f, "Unknown action \"{}\" in {}", name, CONTROLS_FILE),
+            BindingsError::BadBinding(line) => write!(f, "Line {} of {} is not a valid binding", line, CONTROLS_FILE),
+        }
+    }
+}
+
+// Which inputs trigger which actions; an action can have any number of bindings
+#[derive(Clone, PartialEq, Debug)]
+pub struct InputMap {
+    bindings: Vec<(Action, Binding)>,
+}
+
+impl InputMap {
+    pub fn new() -> Self {
+        let slot_keys = [
+            Keycode::Num1, Keycode::Num2, Keycode::Num3,
+            Keycode::Num4, Keycode::Num5, Keycode::Num6,
+            Keycode::Num7, Keycode::Num8, Keycode::Num9,
+        ];
+        let mut bindings = vec![
+            (Action::MoveForward, Binding::key(Keycode::W)),
+            (Action::MoveBackward, Binding::key(Keycode::S)),
+            (Action::MoveLeft, Binding::key(Keycode::A)),
+            (Action::MoveRight, Binding::key(Keycode::D)),
+            (Action::Jump, Binding::key(Keycode::Space)),
+            (Action::Descend, Binding::key(Keycode::LShift)),
+            (Action::Sprint, Binding::key(Keycode::R)), // Not Ctrl, which would turn Z and Y into undo and redo
+            (Action::Crouch, Binding::key(Keycode::LShift)), // Shares Shift with flying down
+            (Action::Break, Binding::mouse(MouseButton::Left)),
+            (Action::Place, Binding::mouse(MouseButton::Right)),
+            (Action::PickBlock, Binding::mouse(MouseButton::Middle)),
+            (Action::Undo, Binding::ctrl(Keycode::Z)),
+            (Action::Redo, Binding::ctrl(Keycode::Y)),
+            (Action::BuildShape, Binding::key(Keycode::Return)),
+            (Action::ToggleFly, Binding::key(Keycode::F)),
+            (Action::CycleView, Binding::key(Keycode::F5)),
+            (Action::OrbitPivot, Binding::key(Keycode::P)),
+            (Action::AddKeyframe, Binding::key(Keycode::K)),
+            (Action::ToggleHud, Binding::key(Keycode::F3)),
+            (Action::ToggleCapture, Binding::key(Keycode::Escape)),
+            (Action::OpenCommand, Binding::key(Keycode::Slash)),
+        ];
+        for (slot, &key) in slot_keys.iter().enumerate() {
+            bindings.push((Action::HotbarSlot(slot), Binding::key(key)));
+        }
+        Self { bindings }
+    }
+
+    pub fn bindings_for(&self, action: Action) -> Vec<Binding> {
+        self.bindings.iter().filter(|(a, _)| *a == action).map(|&(_, b)| b).collect()
+    }
+
+    // Bind `binding` to `action`, taking it away from any other action
+    pub fn bind(&mut self, action: Action, binding: Binding) {
+        self.bindings.retain(|&(_, b)| b != binding);
+        self.bindings.push((action, binding));
+    }
+
+    pub fn unbind(&mut self, action: Action, binding: Binding) {
+        self.bindings.retain(|&(a, b)| !(a == action && b == binding));
+    }
+
+    pub fn is_held(&self, action: Action, keys: &[Keycode], mouse: &[MouseButton]) -> bool {
+        self.bindings.iter().any(|(a, b)| *a == action && b.is_held(keys, mouse))
+    }
+
+    // Actions started by pressing `input`. Ctrl and Alt must match exactly so Ctrl+Z doesn't
+    // also fire a plain Z binding; Shift only matters to bindings that ask for it. A Ctrl or Alt
+    // key bound on its own (say Ctrl to sprint) is only counted if a binding needs it, so
+    // clicking still breaks blocks while sprinting.
+    pub fn pressed(&self, input: Input, held: ModifierKeys) -> Vec<Action> {
+        let held = held.without_input(input);
+        let actions = self.matching(input, held);
+        let relaxed = ModifierKeys {
+            ctrl: held.ctrl && !self.binds_alone(Keycode::LCtrl, Keycode::RCtrl),
+            alt: held.alt && !self.binds_alone(Keycode::LAlt, Keycode::RAlt),
+            ..held
+        };
+        if actions.is_empty() && relaxed != held {
+            self.matching(input, relaxed)
+        } else {
+            actions
+        }
+    }
+
+    fn matching(&self, input: Input, held: ModifierKeys) -> Vec<Action> {
+        self.bindings
+            .iter()
+            .filter(|(_, b)| {
+                b.input == input
+                    && b.modifiers.ctrl == held.ctrl
+                    && b.modifiers.alt == held.alt
+                    && (!b.modifiers.shift || held.shift)
+            })
+            .map(|&(a, _)| a)
+            .collect()
+    }
+
+    // Whether either key is bound as an action of its own
+    fn binds_alone(&self, left: Keycode, right: Keycode) -> bool {
+        self.bindings.iter().any(|(_, b)| b.input == Input::Key(left) || b.input == Input::Key(right))
+    }
+
+    // Actions ended by letting go of `input`, whatever modifiers are held by then
+    pub fn released(&self, input: Input) -> Vec<Action> {
+        self.bindings.iter().filter(|(_, b)| b.input == input).map(|&(a, _)| a).collect()
+    }
+
+    // Every binding of `action`, as shown to the player
+    pub fn describe(&self, action: Action) -> String {
+        let names: Vec<String> = self.bindings_for(action).iter().map(|b| b.to_string()).collect();
+        if names.is_empty() { "(unbound)".to_string() } else { names.join(" / ") }
+    }
+
+    // "Keys - Action" lines for the on-screen help, hotbar slots folded into one line
+    pub fn help_lines(&self) -> Vec<String> {
+        let describe = |action: Action| self.describe(action);
+        let mut lines: Vec<String> = Action::all()
+            .into_iter()
+            .filter(|action| !matches!(action, Action::HotbarSlot(_)))
+            .map(|action| format!("{} - {}", describe(action), action.label()))
+            .collect();
+        let slots: Vec<String> = (0..HOTBAR_SLOTS).map(|slot| describe(Action::HotbarSlot(slot))).collect();
+        lines.push(format!("{} / Mouse Wheel - Select Hotbar Slot", slots.join(" ")));
+        lines
+    }
+
+    // One `action binding` pair per line, `#` starts a comment. Unbound actions are written as
+    // `action none` so loading doesn't give them their defaults back.
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# Controls: action followed by one key or mouse button\n");
+        for action in Action::all() {
+            let bindings = self.bindings_for(action);
+            if bindings.is_empty() {
+                text += &format!("{} none\n", action.id());
+            }
+            for binding in bindings {
+                text += &format!("{} {}\n", action.id(), binding);
+            }
+        }
+        text
+    }
+
+    // Actions listed in the file replace their defaults; the rest (such as actions added since the
+    // file was saved) keep them. A default loses its input if the file gives that input to
+    // something else.
+    pub fn from_text(text: &str) -> Result<InputMap, BindingsError> {
+        let actions = Action::all();
+        let defaults = InputMap::new();
+        let mut map = InputMap::new();
+        let mut loaded = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (id, binding) = line.split_once(' ').ok_or(BindingsError::BadBinding(number + 1))?;
+            let action = actions
+                .iter()
+                .copied()
+                .find(|a| a.id() == id)
+                .ok_or_else(|| BindingsError::UnknownAction(id.to_string()))?;
+            if !loaded.contains(&action) {
+                map.bindings.retain(|&(a, _)| a != action);
+                loaded.push(action);
+            }
+            if binding.trim() == "none" {
+                continue;
+            }
+            let binding = Binding::parse(binding).ok_or(BindingsError::BadBinding(number + 1))?;
+            if !defaults.bindings.contains(&(action, binding)) {
+                map.bindings.retain(|&(a, b)| b != binding || loaded.contains(&a));
+            }
+            map.bindings.push((action, binding));
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctrl() -> ModifierKeys {
+        ModifierKeys { ctrl: true, ..Default::default() }
+    }
+
+    #[test]
+    fn ctrl_bindings_need_ctrl_and_plain_ones_refuse_it() {
+        let mut map = InputMap::new();
+        map.bind(Action::AddKeyframe, Binding::key(Keycode::Z));
+        assert_eq!(map.pressed(Input::Key(Keycode::Z), ctrl()), vec![Action::Undo]);
+        assert_eq!(map.pressed(Input::Key(Keycode::Z), ModifierKeys::default()), vec![Action::AddKeyframe]);
+    }
+
+    #[test]
+    fn held_actions_ignore_extra_modifiers() {
+        let map = InputMap::new();
+        let keys = [Keycode::W, Keycode::LShift];
+        assert!(map.is_held(Action::MoveForward, &keys, &[]));
+        assert!(map.is_held(Action::Descend, &keys, &[]));
+        assert!(!map.is_held(Action::MoveLeft, &keys, &[]));
+        assert!(map.is_held(Action::Break, &[], &[MouseButton::Left]));
+    }
+
+    #[test]
+    fn modifier_keys_can_be_bound_on_their_own() {
+        let mut map = InputMap::new();
+        let binding = Binding::new(Input::Key(Keycode::LCtrl), ctrl());
+        assert!(!binding.modifiers.ctrl);
+        map.bind(Action::Jump, binding);
+        assert!(map.pressed(Input::Key(Keycode::LCtrl), ctrl()).contains(&Action::Jump));
+    }
+
+    #[test]
+    fn ctrl_held_for_sprint_doesnt_block_plain_bindings() {
+        let mut map = InputMap::new();
+        map.bind(Action::Sprint, Binding::key(Keycode::LCtrl));
+        assert_eq!(map.pressed(Input::Mouse(MouseButton::Left), ctrl()), vec![Action::Break]);
+        assert_eq!(map.pressed(Input::Key(Keycode::Num3), ctrl()), vec![Action::HotbarSlot(2)]);
+        assert_eq!(map.pressed(Input::Key(Keycode::Z), ctrl()), vec![Action::Undo]);
+        // Without Ctrl bound on its own, Ctrl still keeps plain bindings from firing
+        assert!(InputMap::new().pressed(Input::Mouse(MouseButton::Left), ctrl()).is_empty());
+    }
+
+    #[test]
+    fn rebinding_moves_an_input_between_actions() {
+        let mut map = InputMap::new();
+        map.bind(Action::Jump, Binding::key(Keycode::W));
+        assert!(map.bindings_for(Action::MoveForward).is_empty());
+        assert_eq!(map.bindings_for(Action::Jump).len(), 2);
+        map.unbind(Action::Jump, Binding::key(Keycode::Space));
+        assert_eq!(map.bindings_for(Action::Jump), vec![Binding::key(Keycode::W)]);
+    }
+
+    #[test]
+    fn file_round_trip_and_errors() {
+        let mut map = InputMap::new();
+        map.bind(Action::Place, Binding::new(Input::Mouse(MouseButton::X1), ModifierKeys { shift: true, ..Default::default() }));
+        assert_eq!(InputMap::from_text(&map.to_text()).unwrap().to_text(), map.to_text());
+        assert_eq!(InputMap::from_text("fly W"), Err(BindingsError::UnknownAction("fly".to_string())));
+        assert_eq!(InputMap::from_text("jump\n"), Err(BindingsError::BadBinding(1)));
+    }
+
+    #[test]
+    fn files_only_override_the_actions_they_list() {
+        // An older file without sprint or the command line
+        let map = InputMap::from_text("jump J\nplace /\ndescend Left Shift\nbreak none").unwrap();
+        assert_eq!(map.bindings_for(Action::Jump), vec![Binding::key(Keycode::J)]);
+        assert_eq!(map.bindings_for(Action::MoveForward), vec![Binding::key(Keycode::W)]);
+        assert_eq!(map.bindings_for(Action::Sprint), InputMap::new().bindings_for(Action::Sprint));
+        assert!(map.bindings_for(Action::Break).is_empty());
+        // Slash was taken by the file, but Shift is still shared as it is by default
+        assert!(map.bindings_for(Action::OpenCommand).is_empty());
+        assert_eq!(map.bindings_for(Action::Crouch), vec![Binding::key(Keycode::LShift)]);
+        assert_eq!(InputMap::from_text(&map.to_text()).unwrap().to_text(), map.to_text());
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use crate::VoxelType;
+
+// Blocks carried in survival, gained by breaking and spent by placing
+pub struct Inventory {
+    counts: HashMap<VoxelType, u32>,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self { counts: HashMap::new() }
+    }
+
+    pub fn count(&self, voxel_type: VoxelType) -> u32 {
+        self.counts.get(&voxel_type).copied().unwrap_or(0)
+    }
+
+    pub fn add(&mut self, voxel_type: VoxelType) {
+        *self.counts.entry(voxel_type).or_insert(0) += 1;
+    }
+
+    // Use up one block, or return false if there are none left
+    pub fn take(&mut self, voxel_type: VoxelType) -> bool {
+        match self.counts.get_mut(&voxel_type) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_are_spent_one_at_a_time() {
+        let mut inventory = Inventory::new();
+        inventory.add(VoxelType::Stone);
+        inventory.add(VoxelType::Stone);
+        assert_eq!(inventory.count(VoxelType::Stone), 2);
+        assert!(inventory.take(VoxelType::Stone));
+        assert!(inventory.take(VoxelType::Stone));
+        assert!(!inventory.take(VoxelType::Stone));
+        assert_eq!(inventory.count(VoxelType::Stone), 0);
+    }
+
+    #[test]
+    fn cannot_take_what_was_never_gathered() {
+        let mut inventory = Inventory::new();
+        inventory.add(VoxelType::Dirt);
+        assert!(!inventory.take(VoxelType::Wood));
+        inventory.clear();
+        assert_eq!(inventory.count(VoxelType::Dirt), 0);
+    }
+}
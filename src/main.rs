@@ -14,18 +14,51 @@ use std::{fs, os::unix::raw::time_t, sync::Arc, time::Instant};
 
 use epi::backend::FrameData;
 use glm::{vec3, Vec3, Vector3};
-use sdl2::{event::WindowEvent, keyboard::Keycode, sys::u_int};
+use sdl2::{event::WindowEvent, keyboard::Keycode, mouse::MouseButton, sys::u_int};
 // Alias the backend to something less mouthful
 use egui_sdl2_gl::{self as egui_backend, painter::{compile_shader, link_program}};
 use gl::types::*;
 use std::ptr;
 use std::ffi::CString;
+mod brush;
+mod camera_path;
+mod camera_rig;
+mod chunk_workers;
+mod flood_fill;
+mod frame_clock;
+mod commands;
+mod gamepad;
+mod input_map;
+mod inventory;
+mod placement;
+mod player;
+mod shapes;
+mod terrain;
 mod window_manager;
-use window_manager::{window_manager::windows::{MainWindow, SandboxWindow}, *};
+mod world_edit;
+use window_manager::{window_manager::windows::{HudInfo, MainWindow, SandboxWindow}, *};
 use crate::window_manager::window_manager::windows::ShaderType;
+use terrain::TerrainSettings;
+use chunk_workers::ChunkWorkers;
+use brush::{BrushStroke, StrokeAction};
+use world_edit::{EditHistory, WorldEdit};
+use placement::{Aabb, PlacementRules};
+use frame_clock::{FrameClock, FIXED_TIMESTEP};
+use player::{Player, CROUCH_SPEED, SPRINT_SPEED};
+use camera_rig::{CameraRig, CameraView};
+use camera_path::Keyframe;
+use commands::Command;
+use gamepad::{GamepadAction, Gamepads};
+use input_map::{Action, Binding, Input, InputMap, ModifierKeys, CONTROLS_FILE};
+use inventory::Inventory;
+
+// How far (in chunks) from the camera chunks are kept queued for generation
+const LOAD_RADIUS: i32 = 4;
+// Maximum number of generated chunks added to the world per frame
+const CHUNKS_PER_FRAME: usize = 2;
 
 // Voxel types
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 enum VoxelType {
     Air,
     Dirt,
@@ -34,6 +67,75 @@ enum VoxelType {
     Wood,
     Leaves,
     Light,  // New light block type
+    CoalOre,
+    IronOre,
+    GoldOre,
+    DiamondOre,
+    Water,
+}
+
+impl VoxelType {
+    // Liquids are transparent and can be passed through or replaced
+    fn is_liquid(self) -> bool {
+        self == VoxelType::Water
+    }
+
+    // Solid blocks stop rays and movement
+    fn is_solid(self) -> bool {
+        self != VoxelType::Air && !self.is_liquid()
+    }
+
+    // Seconds of holding the break button needed to break the block in survival mode
+    fn hardness(self) -> f32 {
+        match self {
+            VoxelType::Air | VoxelType::Water => 0.0,
+            VoxelType::Leaves => 0.2,
+            VoxelType::Light => 0.3,
+            VoxelType::Dirt => 0.5,
+            VoxelType::Grass => 0.6,
+            VoxelType::Stone => 1.5,
+            VoxelType::Wood => 2.0,
+            VoxelType::CoalOre => 2.5,
+            VoxelType::IronOre | VoxelType::GoldOre => 3.0,
+            VoxelType::DiamondOre => 4.0,
+        }
+    }
+}
+
+// How the player interacts with the world
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum GameMode {
+    Creative,  // Instant breaking, infinite blocks, flight and the editing tools
+    Survival,  // Walking only; blocks take time to break and placing uses up the inventory
+    Spectator, // Flies through everything and can't change the world
+}
+
+impl GameMode {
+    const ALL: [GameMode; 3] = [GameMode::Creative, GameMode::Survival, GameMode::Spectator];
+
+    fn name(self) -> &'static str {
+        match self {
+            GameMode::Creative => "creative",
+            GameMode::Survival => "survival",
+            GameMode::Spectator => "spectator",
+        }
+    }
+
+    // Movement mode the rules insist on, if the player can't choose
+    fn movement_mode(self) -> Option<MovementMode> {
+        match self {
+            GameMode::Creative => None,
+            GameMode::Survival => Some(MovementMode::Player),
+            GameMode::Spectator => Some(MovementMode::Fly),
+        }
+    }
+}
+
+// Survival breaking progress on the targeted block
+#[derive(Clone, Copy)]
+struct BreakProgress {
+    voxel: (i32, i32, i32),
+    elapsed: f32,
 }
 
 // Voxel data structure
@@ -45,6 +147,7 @@ struct Voxel {
 struct Chunk {
     voxels: Vec<Voxel>,
     position: (i32, i32, i32), // Chunk position in world
+    generated: bool,           // False for chunks made by editing before their terrain arrived
 }
 
 impl Chunk {
@@ -53,7 +156,7 @@ impl Chunk {
         for _ in 0..16 * 16 * 16 {
             voxels.push(Voxel { voxel_type: VoxelType::Air });
         }
-        Self { voxels, position }
+        Self { voxels, position, generated: false }
     }
 
     fn get_voxel(&self, x: usize, y: usize, z: usize) -> &Voxel {
@@ -71,46 +174,65 @@ impl Chunk {
     }
 }
 
+// Result of a voxel raycast
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct RaycastHit {
+    voxel: (i32, i32, i32),   // Block that was hit
+    voxel_type: VoxelType,
+    normal: (i32, i32, i32),  // Normal of the face the ray entered through (zero if it started inside)
+    distance: f32,            // Distance along the ray to the entered face
+    place: (i32, i32, i32),   // Cell next to the hit face, where a new block would go
+}
+
+// How far the player can reach when breaking and placing blocks
+const REACH_DISTANCE: f32 = 10.0;
+// Field of view added while sprinting (degrees), and how quickly it comes and goes (per second)
+const SPRINT_FOV_KICK: f32 = 10.0;
+const FOV_KICK_RATE: f32 = 8.0;
+// Buffer value of shape tool preview blocks (one past the last real voxel type)
+const PREVIEW_VOXEL: i32 = 12;
+
 // World data structure
 struct World {
     chunks: Vec<Chunk>,
+    game_mode: GameMode, // Rules this world is played by
 }
 
 impl World {
     fn new() -> Self {
-        let mut world = Self { chunks: Vec::new() };
-        // Create a 3x3 grid of chunks on the same Y level (y=0)
+        // Chunks are generated in the background and added with insert_chunk
+        Self { chunks: Vec::new(), game_mode: GameMode::Creative }
+    }
+
+    // The 3x3 grid of chunk positions on the same Y level (y=0) that make up the world
+    fn chunk_positions() -> Vec<(i32, i32, i32)> {
+        let mut positions = Vec::new();
         for x in -1..=1 {
             for z in -1..=1 {
-                let mut chunk = Chunk::new((x, 0, z));
-                // Generate some terrain
-                for cx in 0..16 {
-                    for cz in 0..16 {
-                        // Calculate absolute world position
-                        let world_x = cx as f32 + (x * 16) as f32;
-                        let world_z = cz as f32 + (z * 16) as f32;
-                        
-                        // Generate height using world coordinates
-                        let height = 4.0 + (world_x * 0.1).sin() * 1.0 + (world_z * 0.1).cos() * 1.0;  // Reduced height variation
-                        
-                        for cy in 0..16 {
-                            let cy_f32 = cy as f32;
-                            if cy_f32 <= height {
-                                if cy_f32 > height - 1.0 {
-                                    chunk.set_voxel(cx, cy, cz, VoxelType::Grass);
-                                } else if cy_f32 > height - 3.0 {  // Reduced dirt layer
-                                    chunk.set_voxel(cx, cy, cz, VoxelType::Dirt);
-                                } else {
-                                    chunk.set_voxel(cx, cy, cz, VoxelType::Stone);
-                                }
-                            }
-                        }
-                    }
+                positions.push((x, 0, z));
+            }
+        }
+        positions
+    }
+
+    // Whether the chunk's terrain has arrived; editing can create a chunk before that
+    fn has_chunk(&self, position: (i32, i32, i32)) -> bool {
+        self.chunks.iter().any(|chunk| chunk.position == position && chunk.generated)
+    }
+
+    // Add a generated chunk, keeping any blocks already placed there before it arrived
+    fn insert_chunk(&mut self, mut chunk: Chunk) {
+        chunk.generated = true;
+        if let Some(existing) = self.chunks.iter_mut().find(|c| c.position == chunk.position) {
+            for (voxel, edited) in chunk.voxels.iter_mut().zip(&existing.voxels) {
+                if edited.voxel_type != VoxelType::Air {
+                    voxel.voxel_type = edited.voxel_type;
                 }
-                world.chunks.push(chunk);
             }
+            *existing = chunk;
+        } else {
+            self.chunks.push(chunk);
         }
-        world
     }
 
     fn get_voxel(&self, x: i32, y: i32, z: i32) -> VoxelType {
@@ -135,6 +257,65 @@ impl World {
         VoxelType::Air
     }
 
+    // Exact grid traversal (Amanatides & Woo) returning the first block the ray enters.
+    // Liquids are passed through unless `target_liquids` is set.
+    fn raycast(&self, origin: Vec3, direction: Vec3, max_distance: f32, target_liquids: bool) -> Option<RaycastHit> {
+        let length = glm::length(direction);
+        if length == 0.0 {
+            return None;
+        }
+        let origin = [origin.x, origin.y, origin.z];
+        let direction = [direction.x / length, direction.y / length, direction.z / length];
+
+        let mut cell = [origin[0].floor() as i32, origin[1].floor() as i32, origin[2].floor() as i32];
+        let mut step = [0; 3];
+        let mut t_max = [f32::INFINITY; 3];   // Distance to the next cell boundary on each axis
+        let mut t_delta = [f32::INFINITY; 3]; // Distance between cell boundaries on each axis
+        for axis in 0..3 {
+            if direction[axis] > 0.0 {
+                step[axis] = 1;
+                t_max[axis] = ((cell[axis] + 1) as f32 - origin[axis]) / direction[axis];
+                t_delta[axis] = 1.0 / direction[axis];
+            } else if direction[axis] < 0.0 {
+                step[axis] = -1;
+                t_max[axis] = (origin[axis] - cell[axis] as f32) / -direction[axis];
+                t_delta[axis] = -1.0 / direction[axis];
+            }
+        }
+
+        let mut normal = [0; 3];
+        let mut distance = 0.0;
+        loop {
+            let voxel_type = self.get_voxel(cell[0], cell[1], cell[2]);
+            if voxel_type.is_solid() || (target_liquids && voxel_type.is_liquid()) {
+                return Some(RaycastHit {
+                    voxel: (cell[0], cell[1], cell[2]),
+                    voxel_type,
+                    normal: (normal[0], normal[1], normal[2]),
+                    distance,
+                    place: (cell[0] + normal[0], cell[1] + normal[1], cell[2] + normal[2]),
+                });
+            }
+
+            // Step into the neighbouring cell whose boundary is closest
+            let axis = if t_max[0] < t_max[1] {
+                if t_max[0] < t_max[2] { 0 } else { 2 }
+            } else if t_max[1] < t_max[2] {
+                1
+            } else {
+                2
+            };
+            distance = t_max[axis];
+            if distance > max_distance {
+                return None;
+            }
+            cell[axis] += step[axis];
+            t_max[axis] += t_delta[axis];
+            normal = [0; 3];
+            normal[axis] = -step[axis];
+        }
+    }
+
     fn set_voxel(&mut self, x: i32, y: i32, z: i32, voxel_type: VoxelType) {
         let chunk_x = (x as f32 / 16.0).floor() as i32;
         let chunk_y = (y as f32 / 16.0).floor() as i32;
@@ -159,7 +340,50 @@ impl World {
     }
 }
 
+// Movement requested for one tick, independent of which keys (or sticks) produced it.
+// Each axis runs from -1 to 1.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+struct MovementIntent {
+    forward: f32, // Backward is negative
+    right: f32,   // Left is negative
+    up: f32,      // Down is negative
+}
+
+impl MovementIntent {
+    fn from_keys(forward: bool, backward: bool, left: bool, right: bool, up: bool, down: bool) -> Self {
+        let axis = |positive: bool, negative: bool| positive as i32 as f32 - negative as i32 as f32;
+        Self {
+            forward: axis(forward, backward),
+            right: axis(right, left),
+            up: axis(up, down),
+        }
+    }
+
+    // Add analog input (such as a gamepad stick) on top, keeping each axis within -1..1
+    fn combined(self, forward: f32, right: f32, up: f32) -> Self {
+        let clamp = |v: f32| v.clamp(-1.0, 1.0);
+        Self {
+            forward: clamp(self.forward + forward),
+            right: clamp(self.right + right),
+            up: clamp(self.up + up),
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.forward == 0.0 && self.right == 0.0 && self.up == 0.0
+    }
+}
+
+// How movement input is mapped onto the camera's orientation
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum MovementMode {
+    Walk,   // Forward and strafing stay level, whatever the pitch
+    Fly,    // Forward follows the view direction
+    Player, // Level movement with gravity and collision, driven by the player controller
+}
+
 // Camera structure
+#[derive(Clone)]
 struct Camera {
     position: Vec3,
     front: Vec3,
@@ -167,8 +391,16 @@ struct Camera {
     right: Vec3,
     yaw: f32,
     pitch: f32,
-    movement_speed: f32,
+    movement_speed: f32,    // Top speed in blocks per second
     mouse_sensitivity: f32,
+    movement_mode: MovementMode,
+    velocity: Vec3,
+    acceleration: f32,      // How quickly velocity reaches the requested speed (per second)
+    damping: f32,           // How quickly velocity dies away without input (per second)
+    fov: f32,               // Vertical field of view in degrees
+    aspect: f32,            // Viewport width over height
+    near: f32,
+    far: f32,
 }
 
 impl Camera {
@@ -187,41 +419,57 @@ impl Camera {
             pitch: -30.0,  // Start looking down at the terrain
             movement_speed: 2.0,
             mouse_sensitivity: 0.1,
+            movement_mode: MovementMode::Fly,
+            velocity: vec3(0.0, 0.0, 0.0),
+            acceleration: 12.0,
+            damping: 8.0,
+            fov: 70.0,
+            aspect: 1280.0 / 700.0,
+            near: 0.1,
+            far: 100.0,
         }
     }
     
-    fn process_keyboard(&mut self, direction: &str, delta_time: f32) {
-        // Ensure delta_time is reasonable to prevent huge jumps
-        let delta_time = delta_time.min(0.1);
-        
-        // Calculate base velocity with a fixed time step
-        let base_velocity = self.movement_speed * delta_time;
-        
-        // Scale velocity based on direction
-        let velocity = match direction {
-            "FORWARD" | "BACKWARD" => base_velocity * 0.5,  // Reduce forward/backward speed
-            "LEFT" | "RIGHT" => base_velocity * 0.7,       // Slightly reduce strafing speed
-            "UP" | "DOWN" => base_velocity * 0.3,          // Reduce vertical movement speed
-            _ => base_velocity
+    // Direction the intent asks to move in, at most unit length so diagonals aren't faster
+    fn wish_direction(&self, intent: MovementIntent) -> Vec3 {
+        let world_up = vec3(0.0, 1.0, 0.0);
+        let (forward, right) = match self.movement_mode {
+            MovementMode::Fly => (self.front, self.right),
+            MovementMode::Walk | MovementMode::Player => {
+                let level = |v: Vec3| {
+                    let flat = vec3(v.x, 0.0, v.z);
+                    if glm::length(flat) > 0.0 { glm::normalize(flat) } else { flat }
+                };
+                (level(self.front), level(self.right))
+            }
         };
-
-        match direction {
-            "FORWARD" => self.position = self.position + self.front * velocity,
-            "BACKWARD" => self.position = self.position - self.front * velocity,
-            "LEFT" => self.position = self.position - self.right * velocity,
-            "RIGHT" => self.position = self.position + self.right * velocity,
-            "UP" => self.position = self.position + self.up * velocity,
-            "DOWN" => self.position = self.position - self.up * velocity,
-            _ => {}
-        }
+        let wish = forward * intent.forward + right * intent.right + world_up * intent.up;
+        let length = glm::length(wish);
+        if length > 1.0 { wish / length } else { wish }
+    }
+    
+    // Ease velocity towards the speed the intent asks for
+    fn update_velocity(&mut self, intent: MovementIntent, delta_time: f32) {
+        let target = self.wish_direction(intent) * self.movement_speed;
+        let rate = if intent.is_idle() { self.damping } else { self.acceleration };
+        let blend = 1.0 - (-rate * delta_time).exp();
+        self.velocity = self.velocity + (target - self.velocity) * blend;
+    }
+    
+    // Advance one simulation tick of free movement
+    fn apply_movement(&mut self, intent: MovementIntent, delta_time: f32) {
+        self.update_velocity(intent, delta_time);
+        self.position = self.position + self.velocity * delta_time;
     }
     
     fn process_mouse_movement(&mut self, x_offset: f32, y_offset: f32) {
-        let x_offset = x_offset * self.mouse_sensitivity;
-        let y_offset = y_offset * self.mouse_sensitivity;
-        
-        self.yaw += x_offset;
-        self.pitch += y_offset;
+        self.rotate(x_offset * self.mouse_sensitivity, y_offset * self.mouse_sensitivity);
+    }
+    
+    // Turn by the given yaw and pitch in degrees
+    fn rotate(&mut self, yaw: f32, pitch: f32) {
+        self.yaw += yaw;
+        self.pitch += pitch;
         
         // Constrain pitch
         if self.pitch > 89.0 {
@@ -231,7 +479,11 @@ impl Camera {
             self.pitch = -89.0;
         }
         
-        // Update front vector
+        self.update_vectors();
+    }
+    
+    // Recompute the front, right and up vectors from yaw and pitch
+    fn update_vectors(&mut self) {
         let x = self.yaw.to_radians().cos() * self.pitch.to_radians().cos();
         let y = self.pitch.to_radians().sin();
         let z = self.yaw.to_radians().sin() * self.pitch.to_radians().cos();
@@ -241,19 +493,34 @@ impl Camera {
     }
     
     fn get_view_matrix(&self) -> glm::Mat4 {
-        // Create a look-at matrix manually since glm::look_at is not available
-        let f = glm::normalize(self.front);
-        let r = glm::normalize(glm::cross(f, vec3(0.0, 1.0, 0.0)));
-        let u = glm::cross(r, f);
-        
-        // Create view matrix directly
-        let mut view = glm::Mat4::new(
-            glm::vec4(r.x, r.y, r.z, -glm::dot(r, self.position)),
-            glm::vec4(u.x, u.y, u.z, -glm::dot(u, self.position)),
-            glm::vec4(-f.x, -f.y, -f.z, glm::dot(f, self.position)),
-            glm::vec4(0.0, 0.0, 0.0, 1.0)
-        );
-        
+        glm::ext::look_at(self.position, self.position + self.front, vec3(0.0, 1.0, 0.0))
+    }
+    
+    fn get_projection_matrix(&self) -> glm::Mat4 {
+        glm::ext::perspective(self.fov.to_radians(), self.aspect, self.near, self.far)
+    }
+    
+    // World-space direction of the ray through a point in normalized device coordinates
+    // (-1..1 on both axes), matching the ray the shaders cast for that pixel
+    fn ray_direction(&self, ndc_x: f32, ndc_y: f32) -> Vec3 {
+        let target = glm::inverse(&self.get_projection_matrix()) * glm::vec4(ndc_x, ndc_y, 1.0, 1.0);
+        let view_dir = glm::normalize(vec3(target.x, target.y, target.z) / target.w);
+        let world = glm::inverse(&self.get_view_matrix()) * glm::vec4(view_dir.x, view_dir.y, view_dir.z, 0.0);
+        glm::normalize(vec3(world.x, world.y, world.z))
+    }
+    
+    // Direction used for picking, through the crosshair at the center of the screen
+    fn pick_direction(&self) -> Vec3 {
+        self.ray_direction(0.0, 0.0)
+    }
+    
+    // Copy of this camera moved to `position` and looking along `front`, with the same lens
+    fn viewed_from(&self, position: Vec3, front: Vec3) -> Camera {
+        let mut view = self.clone();
+        view.position = position;
+        view.front = glm::normalize(front);
+        view.right = glm::normalize(glm::cross(view.front, vec3(0.0, 1.0, 0.0)));
+        view.up = glm::normalize(glm::cross(view.right, view.front));
         view
     }
 }
@@ -269,7 +536,6 @@ fn main() {
     gl_attr.set_double_buffer(true);
     gl_attr.set_multisample_samples(4);
     gl_attr.set_context_version(3, 2);
-        let last_frame_time: Instant = Instant::now();
     let window = video_subsystem
         .window(
             "Voxel Game",
@@ -295,6 +561,9 @@ fn main() {
         egui_backend::with_sdl2(&window, ShaderVersion::Default, DpiScaling::Default);
     let egui_ctx = egui::Context::default();
     let mut event_pump = sdl_context.event_pump().unwrap();
+    // Controllers are optional; without the subsystem the game just runs on keyboard and mouse
+    let mut gamepads = sdl_context.game_controller().ok().map(Gamepads::new);
+    let mut capture_modifier: Option<Keycode> = None; // Modifier pressed while capturing a binding
     let start_time: Instant = Instant::now();
     let repaint_signal = Arc::new(Signal::default());
 
@@ -348,8 +617,24 @@ fn main() {
     }
     
     // Create world and camera
+    let mut terrain_settings = Arc::new(TerrainSettings::new());
     let mut world = World::new();
+    let mut chunk_workers = ChunkWorkers::new(ChunkWorkers::default_thread_count());
     let mut camera = Camera::new();
+    let mut player = Player::from_eye((camera.position.x, camera.position.y, camera.position.z));
+    let mut camera_rig = CameraRig::new();
+    // Frames drawn and seconds spent during the current camera path playback
+    let mut playback_stats = (0u32, 0.0f32);
+    // Extra field of view from sprinting, in degrees
+    let mut fov_kick = 0.0f32;
+    
+    // Block edits: the stroke being painted and everything that can be undone
+    let mut stroke: Option<BrushStroke> = None;
+    let mut edit_history = EditHistory::new();
+    let mut break_held = false;
+    let mut break_progress: Option<BreakProgress> = None;
+    // Blocks gathered in survival
+    let mut inventory = Inventory::new();
     
     // Create world data buffer
     let mut world_buffer = 0;
@@ -376,14 +661,20 @@ fn main() {
     // Pass mutable reference to `MainWindow`
     let mut main_window = MainWindow::new(&mut sandbox_windowi);
     
-    let now: Instant = Instant::now();
-    let delta_time: f32 = now.duration_since(last_frame_time).as_secs_f32();
+    // Saved controls load on top of the defaults, so actions missing from the file keep their
+    // default bindings; a broken file keeps the defaults and says why
+    if let Ok(text) = fs::read_to_string(CONTROLS_FILE) {
+        match InputMap::from_text(&text) {
+            Ok(bindings) => main_window.controls.bindings = bindings,
+            Err(error) => main_window.hud.show_message(error.to_string()),
+        }
+    }
     
     let mut current_shader_path = String::new();
+    let mut frame_clock = FrameClock::new();
     
     'running: loop {
-        let timernow: Instant = Instant::now();
-        let timer: f32 = timernow.duration_since(last_frame_time).as_secs_f32();
+        let frame_delta = frame_clock.begin_frame();
         egui_state.input.time = Some(start_time.elapsed().as_secs_f64());
 
         egui_ctx.begin_frame(egui_state.input.take());
@@ -401,36 +692,357 @@ fn main() {
             repaint_signal: repaint_signal.clone(),
         });
 
+        // Refresh the terrain preview texture before the UI draws it
+        main_window.terrain_preview.update_texture(&mut painter);
+
         // Process UI first
         main_window.ui(&egui_ctx);
         
+        // Regenerate the loaded world when new terrain settings are applied
+        if main_window.terrain_preview.apply_requested {
+            main_window.terrain_preview.apply_requested = false;
+            terrain_settings = Arc::new(main_window.terrain_preview.settings.clone());
+            chunk_workers.cancel_all();
+            world = World::new();
+            stroke = None;
+            edit_history.clear();
+            inventory.clear();
+        }
+        
+        // Run whatever was typed into the command line
+        if let Some(text) = main_window.command_line.submitted.take() {
+            match commands::parse(&text) {
+                Ok(Command::GameMode(mode)) => main_window.sandbox_window.game_mode = mode,
+                Ok(Command::Help) => main_window.hud.show_message(commands::HELP.to_string()),
+                Err(error) => main_window.hud.show_message(error.to_string()),
+            }
+            mouse_captured = true;
+            sdl_context.mouse().set_relative_mouse_mode(true);
+        }
+        
+        // The game mode lives with the world; a new world takes whichever mode is selected.
+        // Switching ends anything the old rules started.
+        if main_window.sandbox_window.game_mode != world.game_mode {
+            world.game_mode = main_window.sandbox_window.game_mode;
+            if let Some(finished) = stroke.take() {
+                edit_history.push(finished.finish());
+            }
+            break_held = false;
+            main_window.hud.show_message(format!("Game mode: {:?}", world.game_mode));
+        }
+        let game_mode = world.game_mode;
+        if let Some(forced) = game_mode.movement_mode() {
+            main_window.sandbox_window.movement_mode = forced;
+        }
+        
+        // Undo would hand back blocks for free, so it's a creative tool. A stroke still being
+        // painted is finished first, so it becomes the step that gets undone.
+        let undo = std::mem::take(&mut main_window.sandbox_window.undo_requested);
+        let redo = std::mem::take(&mut main_window.sandbox_window.redo_requested);
+        if (undo || redo) && game_mode == GameMode::Creative {
+            if let Some(finished) = stroke.take() {
+                edit_history.push(finished.finish());
+            }
+            if undo {
+                edit_history.undo(&mut world);
+            } else {
+                edit_history.redo(&mut world);
+            }
+        }
+        
         // Get the current selected block type and movement settings
         let selected_block = main_window.get_sandbox_window().selected_block;
         let movement_speed = main_window.get_sandbox_window().movement_speed;
         let mouse_sensitivity = main_window.get_sandbox_window().mouse_sensitivity;
+        let target_water = main_window.get_sandbox_window().target_water;
+        let brush_shape = main_window.get_sandbox_window().brush_shape;
+        let brush_radius = main_window.get_sandbox_window().brush_radius;
+        let drag_paint = main_window.get_sandbox_window().drag_paint;
+        let placement_rules = main_window.get_sandbox_window().placement_rules;
+        let fill_mode = main_window.get_sandbox_window().fill_mode && game_mode == GameMode::Creative;
+        let fill_settings = main_window.get_sandbox_window().fill_settings;
         
         // Update camera settings
-        camera.movement_speed = movement_speed;
         camera.mouse_sensitivity = mouse_sensitivity;
+        // Entering player mode puts the player's body where the camera is
+        let movement_mode = main_window.get_sandbox_window().movement_mode;
+        if movement_mode == MovementMode::Player && camera.movement_mode != MovementMode::Player {
+            player = Player::from_eye((camera.position.x, camera.position.y, camera.position.z));
+        }
+        camera.movement_mode = movement_mode;
+        camera.acceleration = main_window.get_sandbox_window().acceleration;
+        camera.damping = main_window.get_sandbox_window().damping;
+        camera.aspect = SCREEN_WIDTH as f32 / SCREEN_HEIGHT as f32;
+        
+        // Switching to orbit circles the block in the middle of the screen, or a point ahead
+        // of the camera if it's aimed at the sky
+        let camera_view = main_window.get_sandbox_window().camera_view;
+        if camera_view == CameraView::Orbit && camera_rig.view != CameraView::Orbit {
+            let view = camera_rig.view_camera(&camera);
+            let pivot = match world.raycast(view.position, view.pick_direction(), camera.far, target_water) {
+                Some(hit) => vec3(hit.voxel.0 as f32 + 0.5, hit.voxel.1 as f32 + 0.5, hit.voxel.2 as f32 + 0.5),
+                None => view.position + view.front * camera_rig.orbit_distance,
+            };
+            camera_rig.start_orbit(&view, pivot);
+        }
+        camera_rig.view = camera_view;
+        camera_rig.boom_length = main_window.get_sandbox_window().boom_length;
+        camera_rig.turntable_speed = main_window.get_sandbox_window().turntable_speed;
+        if let Some(gamepads) = gamepads.as_mut() {
+            gamepads.settings = main_window.get_sandbox_window().gamepad_settings;
+        }
         
-        // Update camera position based on keyboard input
+        // Update camera position based on the held bindings
         let keys: Vec<Keycode> = event_pump
             .keyboard_state()
             .pressed_scancodes()
             .filter_map(Keycode::from_scancode)
             .collect();
+        let mouse_buttons: Vec<MouseButton> = event_pump.mouse_state().pressed_mouse_buttons().collect();
             
-        for key in keys {
-            match key {
-                Keycode::W => camera.process_keyboard("FORWARD", timer),
-                Keycode::S => camera.process_keyboard("BACKWARD", timer),
-                Keycode::A => camera.process_keyboard("LEFT", timer),
-                Keycode::D => camera.process_keyboard("RIGHT", timer),
-                Keycode::Space => camera.process_keyboard("UP", timer),
-                Keycode::LShift => camera.process_keyboard("DOWN", timer),
-                Keycode::Escape => mouse_captured = !mouse_captured,
-                _ => {}
+        // The body stays put while orbiting or following a camera path
+        let bindings = &main_window.controls.bindings;
+        let held = |action| bindings.is_held(action, &keys, &mouse_buttons);
+        let (pad_sprint, pad_crouch) = match &gamepads {
+            Some(gamepads) if mouse_captured => gamepads.stance(),
+            _ => (false, false),
+        };
+        let playing_path = main_window.camera_path.playhead.is_some();
+        let intent = if camera_rig.view == CameraView::Orbit || playing_path {
+            MovementIntent::default()
+        } else {
+            let keys_intent = MovementIntent::from_keys(
+                held(Action::MoveForward),
+                held(Action::MoveBackward),
+                held(Action::MoveLeft),
+                held(Action::MoveRight),
+                held(Action::Jump),
+                held(Action::Descend),
+            );
+            // The gamepad drives the player only while the menus are closed; otherwise it
+            // navigates them
+            if mouse_captured {
+                let (forward, right, up) = gamepads.as_ref().map_or((0.0, 0.0, 0.0), Gamepads::movement);
+                keys_intent.combined(forward, right, up)
+            } else {
+                keys_intent
             }
+        };
+        
+        // Crouching only means something on foot (flying descends instead) and stops a sprint.
+        // Sprinting needs forward movement.
+        let on_foot = camera.movement_mode == MovementMode::Player;
+        let crouching = on_foot && (held(Action::Crouch) || pad_crouch);
+        let sprinting = !crouching && intent.forward > 0.0 && (held(Action::Sprint) || pad_sprint);
+        player.crouching = crouching;
+        camera.movement_speed = movement_speed * if sprinting {
+            SPRINT_SPEED
+        } else if crouching {
+            CROUCH_SPEED
+        } else {
+            1.0
+        };
+        
+        // Sprinting widens the view a little, easing in and out
+        let kick_target = if sprinting { SPRINT_FOV_KICK } else { 0.0 };
+        fov_kick += (kick_target - fov_kick) * (1.0 - (-FOV_KICK_RATE * frame_delta).exp());
+        camera.fov = main_window.get_sandbox_window().fov + fov_kick;
+        
+        // Right stick looks around (or orbits), scaled by frame time like a held key
+        if mouse_captured {
+            let (yaw, pitch) = gamepads.as_ref().map_or((0.0, 0.0), |gamepads| gamepads.look(frame_delta));
+            if yaw != 0.0 || pitch != 0.0 {
+                if camera_rig.view == CameraView::Orbit {
+                    camera_rig.orbit_mouse(yaw, pitch, 1.0);
+                } else {
+                    camera.rotate(yaw, pitch);
+                }
+            }
+        }
+        
+        // Simulation runs in fixed ticks so movement doesn't depend on the frame rate
+        while frame_clock.step() {
+            match camera.movement_mode {
+                MovementMode::Fly | MovementMode::Walk => camera.apply_movement(intent, FIXED_TIMESTEP),
+                MovementMode::Player => {
+                    // Up jumps; the body stays put until the terrain under it has loaded
+                    camera.update_velocity(MovementIntent { up: 0.0, ..intent }, FIXED_TIMESTEP);
+                    let (x, y, z) = player.position;
+                    let feet_chunk = ((x / 16.0).floor() as i32, (y / 16.0).floor() as i32, (z / 16.0).floor() as i32);
+                    if world.has_chunk(feet_chunk) {
+                        player.tick(&world, (camera.velocity.x, camera.velocity.z), intent.up > 0.0, FIXED_TIMESTEP);
+                    }
+                    let (eye_x, eye_y, eye_z) = player.eye();
+                    camera.position = vec3(eye_x, eye_y, eye_z);
+                }
+            }
+        }
+
+        // Camera path recording and playback
+        let camera_path = &mut main_window.camera_path;
+        if camera_path.record_requested {
+            camera_path.record_requested = false;
+            camera_path.record(Keyframe {
+                position: (camera.position.x, camera.position.y, camera.position.z),
+                yaw: camera.yaw,
+                pitch: camera.pitch,
+                fov: camera.fov,
+            });
+        }
+        if camera_path.play_requested {
+            camera_path.play_requested = false;
+            if camera_path.path.keyframes.len() < 2 {
+                main_window.hud.show_message("Record at least two keyframes first".to_string());
+            } else {
+                camera_path.playhead = Some(0.0);
+                playback_stats = (0, 0.0);
+            }
+        }
+        let stopping = camera_path.stop_requested && camera_path.playhead.is_some();
+        camera_path.stop_requested = false;
+        if stopping {
+            camera_path.playhead = None;
+        }
+        if let Some(index) = camera_path.jump_requested.take() {
+            if let Some(&key) = camera_path.path.keyframes.get(index) {
+                camera.position = vec3(key.position.0, key.position.1, key.position.2);
+                camera.yaw = key.yaw;
+                camera.pitch = key.pitch;
+                camera.update_vectors();
+                main_window.sandbox_window.fov = key.fov;
+                camera.fov = key.fov;
+                if camera.movement_mode == MovementMode::Player {
+                    player = Player::from_eye(key.position);
+                }
+            }
+        }
+        if let Some(distance) = camera_path.playhead {
+            // Moves at the path's speed in real time, so a flythrough takes the same time on
+            // any machine and the frame count doubles as a benchmark
+            if let Some(key) = camera_path.path.sample(distance) {
+                camera.position = vec3(key.position.0, key.position.1, key.position.2);
+                camera.yaw = key.yaw;
+                camera.pitch = key.pitch;
+                camera.update_vectors();
+                camera.fov = key.fov;
+            }
+            playback_stats.0 += 1;
+            playback_stats.1 += frame_delta;
+
+            let length = camera_path.path.length();
+            let next = distance + camera_path.path.speed * frame_delta;
+            if next <= length {
+                camera_path.playhead = Some(next);
+            } else if camera_path.looping {
+                camera_path.playhead = Some(if length > 0.0 { next % length } else { 0.0 });
+            } else {
+                camera_path.playhead = None;
+                let (frames, seconds) = playback_stats;
+                main_window.hud.show_message(format!(
+                    "Flythrough finished: {} frames in {:.2} s ({:.1} fps)",
+                    frames,
+                    seconds,
+                    frames as f32 / seconds.max(1e-6)
+                ));
+            }
+        }
+        // The player's body picks up wherever playback left the camera
+        if playing_path && main_window.camera_path.playhead.is_none() && camera.movement_mode == MovementMode::Player {
+            player = Player::from_eye((camera.position.x, camera.position.y, camera.position.z));
+        }
+
+        // The frame is rendered and picked from the rig's view; reach grows with the boom or
+        // orbit distance so the crosshair can still reach blocks near the body. Camera paths
+        // always play back in first person.
+        camera_rig.update(&camera, &world, frame_delta);
+        let (view_camera, reach) = if main_window.camera_path.playhead.is_some() {
+            (camera.clone(), REACH_DISTANCE)
+        } else {
+            (camera_rig.view_camera(&camera), REACH_DISTANCE + camera_rig.eye_offset())
+        };
+
+        // Stream chunks around the camera from the background workers
+        let camera_chunk = (
+            (camera.position.x / 16.0).floor() as i32,
+            (camera.position.y / 16.0).floor() as i32,
+            (camera.position.z / 16.0).floor() as i32,
+        );
+        chunk_workers.set_center(camera_chunk);
+        chunk_workers.cancel_outside(camera_chunk, LOAD_RADIUS);
+        for position in World::chunk_positions() {
+            let in_range = chunk_workers::chebyshev_distance(position, camera_chunk) <= LOAD_RADIUS;
+            if in_range && !world.has_chunk(position) && !chunk_workers.is_pending(position) {
+                chunk_workers.request(position, &terrain_settings);
+            }
+        }
+        for chunk in chunk_workers.poll(CHUNKS_PER_FRAME) {
+            world.insert_chunk(chunk);
+        }
+
+        // Keep painting while a mouse button is held
+        if let Some(active) = stroke.as_mut() {
+            if drag_paint && mouse_captured {
+                if let Some(hit) = world.raycast(view_camera.position, view_camera.pick_direction(), reach, target_water) {
+                    let blockers = [Aabb::player((camera.position.x, camera.position.y, camera.position.z))];
+                    if let Err(error) = active.paint(&mut world, &hit, target_water, &placement_rules, &blockers) {
+                        main_window.hud.show_message(error.to_string());
+                    }
+                }
+            }
+        }
+
+        // Commit the shape tool's current shape as one undoable edit
+        let shape_preview = main_window.shape_tool.preview_cells();
+        if main_window.shape_tool.build_requested {
+            main_window.shape_tool.build_requested = false;
+
+            if game_mode != GameMode::Creative {
+                main_window.hud.show_message("Shapes can only be built in Creative".to_string());
+            } else if shape_preview.is_empty() {
+                main_window.hud.show_message("Set the shape's anchors first".to_string());
+            } else {
+                // Shapes are built in mid-air, so only height, border and overlap limits apply
+                let rules = PlacementRules { require_support: false, ..placement_rules };
+                let blockers = [Aabb::player((camera.position.x, camera.position.y, camera.position.z))];
+                let mut edit = WorldEdit::new();
+                let mut skipped = 0;
+                for &cell in &shape_preview {
+                    match placement::validate(&world, &rules, cell, selected_block, &blockers) {
+                        Ok(()) => edit.set(&mut world, cell, selected_block),
+                        Err(_) => skipped += 1,
+                    }
+                }
+                if skipped > 0 {
+                    main_window.hud.show_message(format!("{} blocks of the shape were blocked", skipped));
+                }
+                edit_history.push(edit);
+                main_window.shape_tool.anchors = [None, None];
+            }
+        }
+
+        // Survival breaking: holding the button wears the targeted block down, restarting
+        // whenever the target changes
+        let breaking_hit = if break_held && mouse_captured && game_mode == GameMode::Survival {
+            world.raycast(view_camera.position, view_camera.pick_direction(), reach, target_water)
+        } else {
+            None
+        };
+        match breaking_hit {
+            Some(hit) => {
+                if break_progress.map(|progress| progress.voxel) != Some(hit.voxel) {
+                    break_progress = Some(BreakProgress { voxel: hit.voxel, elapsed: 0.0 });
+                }
+                let progress = break_progress.as_mut().unwrap();
+                progress.elapsed += frame_delta;
+                if progress.elapsed >= hit.voxel_type.hardness() {
+                    let mut edit = WorldEdit::new();
+                    edit.set(&mut world, hit.voxel, VoxelType::Air);
+                    edit_history.push(edit);
+                    inventory.add(hit.voxel_type);
+                    break_progress = None;
+                }
+            }
+            None => break_progress = None,
         }
 
         // Update world data buffer
@@ -445,6 +1057,12 @@ fn main() {
             
             // Fill the buffer with voxel data
             for chunk in &world.chunks {
+                // Only the 3x3 grid at y = 0 has room in the buffer; anything else would alias a real chunk
+                let (cx, cy, cz) = chunk.position;
+                if !(-1..=1).contains(&cx) || cy != 0 || !(-1..=1).contains(&cz) {
+                    continue;
+                }
+                
                 // Convert chunk coordinates to array indices (0-2 range)
                 let chunk_x = (chunk.position.0 + 1) as usize;  // Convert from -1..1 to 0..2
                 let chunk_z = (chunk.position.2 + 1) as usize;
@@ -462,6 +1080,11 @@ fn main() {
                                 VoxelType::Wood => 4,
                                 VoxelType::Leaves => 5,
                                 VoxelType::Light => 6,
+                                VoxelType::CoalOre => 7,
+                                VoxelType::IronOre => 8,
+                                VoxelType::GoldOre => 9,
+                                VoxelType::DiamondOre => 10,
+                                VoxelType::Water => 11,
                             };
                             
                             // Calculate index in the same way as the shader
@@ -477,6 +1100,20 @@ fn main() {
                 }
             }
             
+            // Show the shape tool preview as ghost blocks in empty space
+            for &(x, y, z) in &shape_preview {
+                let (chunk_x, chunk_z) = ((x as f32 / 16.0).floor() as i32, (z as f32 / 16.0).floor() as i32);
+                if !(-1..=1).contains(&chunk_x) || !(-1..=1).contains(&chunk_z) || !(0..16).contains(&y) {
+                    continue;
+                }
+                let chunk_index = (chunk_x + 1) as usize + (chunk_z + 1) as usize * 3;
+                let local_index = x.rem_euclid(16) as usize + y as usize * 16 + z.rem_euclid(16) as usize * 16 * 16;
+                let index = chunk_index * 16 * 16 * 16 + local_index;
+                if voxel_data[index] == 0 {
+                    voxel_data[index] = PREVIEW_VOXEL;
+                }
+            }
+            
             // Update the buffer with the new data
             gl::BufferSubData(
                 gl::SHADER_STORAGE_BUFFER,
@@ -486,37 +1123,80 @@ fn main() {
             );
         }
 
+        // Find the block the camera is aiming at so the shaders can highlight it
+        let target = world.raycast(view_camera.position, view_camera.pick_direction(), reach, target_water);
+
+        // Draw the HUD on top of the compute output
+        main_window.hud.record_frame_time(frame_clock.delta());
+        main_window.hud.ui(&egui_ctx, &HudInfo {
+            camera_position: (camera.position.x, camera.position.y, camera.position.z),
+            camera_chunk,
+            target: target.map(|hit| (hit.voxel, hit.voxel_type)),
+            selected_block,
+            elapsed_time: frame_clock.elapsed(),
+            sim_ticks: frame_clock.ticks_this_frame(),
+            gamepads: gamepads.as_ref().map_or(0, Gamepads::connected),
+            hud_keys: main_window.controls.bindings.describe(Action::ToggleHud),
+            game_mode,
+            block_count: if game_mode == GameMode::Survival { Some(inventory.count(selected_block)) } else { None },
+        });
+        main_window.sandbox_window.hotbar_ui(&egui_ctx);
+
         unsafe {
             gl::UseProgram(compute_shader_program);
             
             // Set uniforms
             let time_loc = gl::GetUniformLocation(compute_shader_program, CString::new("currentTime").unwrap().as_ptr());
-            gl::Uniform1f(time_loc as GLint, timer);
+            gl::Uniform1f(time_loc as GLint, frame_clock.elapsed());
             
             // Camera position
             let cam_pos_loc = gl::GetUniformLocation(compute_shader_program, CString::new("cameraPosition").unwrap().as_ptr());
-            gl::Uniform3f(cam_pos_loc as GLint, camera.position.x, camera.position.y, camera.position.z);
+            gl::Uniform3f(cam_pos_loc as GLint, view_camera.position.x, view_camera.position.y, view_camera.position.z);
             
             // Camera direction
             let cam_dir_loc = gl::GetUniformLocation(compute_shader_program, CString::new("cameraDirection").unwrap().as_ptr());
-            gl::Uniform3f(cam_dir_loc as GLint, camera.front.x, camera.front.y, camera.front.z);
+            gl::Uniform3f(cam_dir_loc as GLint, view_camera.front.x, view_camera.front.y, view_camera.front.z);
             
             // Camera up
             let cam_up_loc = gl::GetUniformLocation(compute_shader_program, CString::new("cameraUp").unwrap().as_ptr());
-            gl::Uniform3f(cam_up_loc as GLint, camera.up.x, camera.up.y, camera.up.z);
+            gl::Uniform3f(cam_up_loc as GLint, view_camera.up.x, view_camera.up.y, view_camera.up.z);
             
             // Camera right
             let cam_right_loc = gl::GetUniformLocation(compute_shader_program, CString::new("cameraRight").unwrap().as_ptr());
-            gl::Uniform3f(cam_right_loc as GLint, camera.right.x, camera.right.y, camera.right.z);
+            gl::Uniform3f(cam_right_loc as GLint, view_camera.right.x, view_camera.right.y, view_camera.right.z);
             
             // Screen resolution
             let screen_res_loc = gl::GetUniformLocation(compute_shader_program, CString::new("screenResolution").unwrap().as_ptr());
             gl::Uniform2f(screen_res_loc as GLint, SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32);
             
+            // View and projection matrices, plus their inverses for generating rays
+            set_matrix_uniform(compute_shader_program, "viewMatrix", &view_camera.get_view_matrix());
+            set_matrix_uniform(compute_shader_program, "projectionMatrix", &view_camera.get_projection_matrix());
+            set_matrix_uniform(compute_shader_program, "inverseView", &glm::inverse(&view_camera.get_view_matrix()));
+            set_matrix_uniform(compute_shader_program, "inverseProjection", &glm::inverse(&view_camera.get_projection_matrix()));
+            
             // World size
             let world_size_loc = gl::GetUniformLocation(compute_shader_program, CString::new("worldSize").unwrap().as_ptr());
             gl::Uniform3i(world_size_loc as GLint, 3, 1, 3);  // 3x1x3 chunks
 
+            // Targeted block and the face a new block would be placed against
+            let has_target_loc = gl::GetUniformLocation(compute_shader_program, CString::new("hasTarget").unwrap().as_ptr());
+            gl::Uniform1i(has_target_loc as GLint, target.is_some() as GLint);
+            if let Some(hit) = target {
+                let target_block_loc = gl::GetUniformLocation(compute_shader_program, CString::new("targetBlock").unwrap().as_ptr());
+                gl::Uniform3i(target_block_loc as GLint, hit.voxel.0, hit.voxel.1, hit.voxel.2);
+                let target_face_loc = gl::GetUniformLocation(compute_shader_program, CString::new("targetFace").unwrap().as_ptr());
+                gl::Uniform3i(target_face_loc as GLint, hit.normal.0, hit.normal.1, hit.normal.2);
+            }
+            let break_fraction = match (break_progress, target) {
+                (Some(progress), Some(hit)) if progress.voxel == hit.voxel => {
+                    (progress.elapsed / hit.voxel_type.hardness()).min(1.0)
+                }
+                _ => 0.0,
+            };
+            let break_progress_loc = gl::GetUniformLocation(compute_shader_program, CString::new("breakProgress").unwrap().as_ptr());
+            gl::Uniform1f(break_progress_loc as GLint, break_fraction);
+
             gl::DispatchCompute(SCREEN_WIDTH / 8, SCREEN_HEIGHT / 8, 1);
             gl::MemoryBarrier(gl::SHADER_IMAGE_ACCESS_BARRIER_BIT);
         }
@@ -542,6 +1222,215 @@ fn main() {
 
         // Event handling loop
         for event in event_pump.poll_iter() {
+            // While the controls window waits for a new binding, the next key or mouse button
+            // becomes that binding instead of doing anything else. A modifier only counts on its
+            // own if it's let go before anything else is pressed, so Ctrl+Z can be captured.
+            if main_window.controls.capturing.is_none() {
+                capture_modifier = None;
+            } else {
+                let modifiers = ModifierKeys::from_mod(sdl_context.keyboard().mod_state());
+                let binding = match event {
+                    Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
+                        main_window.controls.capturing = None;
+                        continue;
+                    }
+                    Event::KeyDown { keycode: Some(key), repeat: false, .. } if Input::Key(key).is_modifier() => {
+                        capture_modifier = Some(key);
+                        continue;
+                    }
+                    Event::KeyDown { keycode: Some(key), repeat: false, .. } => Some(Binding::new(Input::Key(key), modifiers)),
+                    Event::KeyUp { keycode: Some(key), .. } if capture_modifier == Some(key) => {
+                        Some(Binding::new(Input::Key(key), modifiers))
+                    }
+                    // Clicks on the menus still go to them, so the controls window stays usable
+                    Event::MouseButtonDown { mouse_btn, .. } if !egui_ctx.wants_pointer_input() => {
+                        Some(Binding::new(Input::Mouse(mouse_btn), modifiers))
+                    }
+                    _ => None,
+                };
+                if let Some(binding) = binding {
+                    main_window.controls.capture(binding);
+                    capture_modifier = None;
+                    continue;
+                }
+            }
+            
+            // Keys, mouse buttons and gamepad buttons all turn into actions through the map
+            let bindings = &main_window.controls.bindings;
+            let mut pressed = Vec::new();
+            let mut released = Vec::new();
+            let gamepad_actions = gamepads.as_mut().map_or(Vec::new(), |gamepads| gamepads.handle_event(&event));
+            for gamepad_action in gamepad_actions {
+                match gamepad_action {
+                    GamepadAction::Connected(name) => main_window.hud.show_message(format!("Controller connected: {}", name)),
+                    GamepadAction::Disconnected(name) => main_window.hud.show_message(format!("Controller disconnected: {}", name)),
+                    GamepadAction::Press(action) => pressed.push(action),
+                    GamepadAction::Release(action) => released.push(action),
+                    GamepadAction::PreviousSlot if mouse_captured => main_window.sandbox_window.cycle_slot(-1),
+                    GamepadAction::NextSlot if mouse_captured => main_window.sandbox_window.cycle_slot(1),
+                    GamepadAction::Menu(key, shift) if !mouse_captured => {
+                        let modifiers = egui::Modifiers { shift, ..Default::default() };
+                        for pressed in [true, false] {
+                            egui_state.input.events.push(egui::Event::Key { key, physical_key: None, pressed, repeat: false, modifiers });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            match event {
+                // Typing into a text field, like the command line, doesn't trigger actions
+                Event::KeyDown { keycode: Some(key), keymod, repeat: false, .. } if !egui_ctx.wants_keyboard_input() => {
+                    pressed.extend(bindings.pressed(Input::Key(key), ModifierKeys::from_mod(keymod)));
+                }
+                Event::KeyUp { keycode: Some(key), .. } => released.extend(bindings.released(Input::Key(key))),
+                Event::MouseButtonDown { mouse_btn, .. } => {
+                    let modifiers = ModifierKeys::from_mod(sdl_context.keyboard().mod_state());
+                    pressed.extend(bindings.pressed(Input::Mouse(mouse_btn), modifiers));
+                }
+                Event::MouseButtonUp { mouse_btn, .. } => released.extend(bindings.released(Input::Mouse(mouse_btn))),
+                _ => {}
+            }
+            
+            for action in pressed {
+                match action {
+                    // Picks the targeted block into the active hotbar slot, whatever the tool
+                    Action::PickBlock if mouse_captured => {
+                        if let Some(hit) = world.raycast(view_camera.position, view_camera.pick_direction(), reach, target_water) {
+                            main_window.sandbox_window.assign_to_active_slot(hit.voxel_type);
+                        }
+                    }
+                    
+                    // With the shape tool enabled, breaking and placing set its anchors instead
+                    Action::Break | Action::Place if mouse_captured && game_mode == GameMode::Creative && main_window.shape_tool.enabled => {
+                        let slot = if action == Action::Break { 0 } else { 1 };
+                        if let Some(hit) = world.raycast(view_camera.position, view_camera.pick_direction(), reach, target_water) {
+                            main_window.shape_tool.anchors[slot] = Some(hit.place);
+                        }
+                    }
+                    Action::Break | Action::Place if mouse_captured && fill_mode => {
+                        // Breaking recolours the clicked region, placing fills the space in front of it
+                        let hit = world.raycast(view_camera.position, view_camera.pick_direction(), reach, target_water);
+                        let start = match (action, hit) {
+                            (Action::Break, Some(hit)) => Some(hit.voxel),
+                            (Action::Place, Some(hit)) if hit.normal != (0, 0, 0) => Some(hit.place),
+                            _ => None,
+                        };
+                        if let Some(start) = start {
+                            match flood_fill::flood_fill_cells(&world, start, &fill_settings) {
+                                Ok(cells) => {
+                                    let filling_space = !world.get_voxel(start.0, start.1, start.2).is_solid();
+                                    let rules = PlacementRules { require_support: false, ..placement_rules };
+                                    let blockers = [Aabb::player((camera.position.x, camera.position.y, camera.position.z))];
+                                    let mut edit = WorldEdit::new();
+                                    for cell in cells {
+                                        // Empty space is checked like placement so the player isn't buried
+                                        if !filling_space || placement::validate(&world, &rules, cell, selected_block, &blockers).is_ok() {
+                                            edit.set(&mut world, cell, selected_block);
+                                        }
+                                    }
+                                    edit_history.push(edit);
+                                }
+                                Err(error) => main_window.hud.show_message(error.to_string()),
+                            }
+                        }
+                    }
+                    // Survival breaks over time (see below) and places single blocks from the inventory
+                    Action::Break if mouse_captured && game_mode == GameMode::Survival => break_held = true,
+                    Action::Place if mouse_captured && game_mode == GameMode::Survival => {
+                        let hit = world
+                            .raycast(view_camera.position, view_camera.pick_direction(), reach, target_water)
+                            .filter(|hit| hit.normal != (0, 0, 0));
+                        if let Some(hit) = hit {
+                            let blockers = [Aabb::player((camera.position.x, camera.position.y, camera.position.z))];
+                            if inventory.count(selected_block) == 0 {
+                                main_window.hud.show_message(format!("No {:?} left to place", selected_block));
+                            } else {
+                                match placement::validate(&world, &placement_rules, hit.place, selected_block, &blockers) {
+                                    Ok(()) => {
+                                        let mut edit = WorldEdit::new();
+                                        edit.set(&mut world, hit.place, selected_block);
+                                        edit_history.push(edit);
+                                        inventory.take(selected_block);
+                                    }
+                                    Err(error) => main_window.hud.show_message(error.to_string()),
+                                }
+                            }
+                        }
+                    }
+                    Action::Break | Action::Place if mouse_captured && game_mode == GameMode::Creative && stroke.is_none() => {
+                        // Break, or place against the face that was hit
+                        let stroke_action = match action {
+                            Action::Break => StrokeAction::Break,
+                            _ => StrokeAction::Place(selected_block),
+                        };
+                        let mut new_stroke = BrushStroke::new(stroke_action, brush_shape, brush_radius);
+                        if let Some(hit) = world.raycast(view_camera.position, view_camera.pick_direction(), reach, target_water) {
+                            // There are no other entities yet, so only the player can be in the way
+                            let blockers = [Aabb::player((camera.position.x, camera.position.y, camera.position.z))];
+                            if let Err(error) = new_stroke.paint(&mut world, &hit, target_water, &placement_rules, &blockers) {
+                                main_window.hud.show_message(error.to_string());
+                            }
+                        }
+                        stroke = Some(new_stroke);
+                    }
+                    
+                    // Handled with the window's buttons at the start of the next frame
+                    Action::Undo => main_window.sandbox_window.undo_requested = true,
+                    Action::Redo => main_window.sandbox_window.redo_requested = true,
+                    Action::BuildShape if main_window.shape_tool.enabled => main_window.shape_tool.build_requested = true,
+                    Action::ToggleFly if mouse_captured && game_mode == GameMode::Creative => {
+                        let sandbox = &mut main_window.sandbox_window;
+                        sandbox.movement_mode = match sandbox.movement_mode {
+                            MovementMode::Walk | MovementMode::Player => MovementMode::Fly,
+                            MovementMode::Fly => MovementMode::Player,
+                        };
+                    }
+                    Action::AddKeyframe if mouse_captured => main_window.camera_path.record_requested = true,
+                    Action::CycleView if mouse_captured => {
+                        let sandbox = &mut main_window.sandbox_window;
+                        sandbox.camera_view = sandbox.camera_view.next();
+                    }
+                    // Moves the orbit pivot to the block under the crosshair
+                    Action::OrbitPivot if mouse_captured && camera_rig.view == CameraView::Orbit => {
+                        if let Some(hit) = world.raycast(view_camera.position, view_camera.pick_direction(), camera.far, target_water) {
+                            let pivot = vec3(hit.voxel.0 as f32 + 0.5, hit.voxel.1 as f32 + 0.5, hit.voxel.2 as f32 + 0.5);
+                            camera_rig.start_orbit(&view_camera, pivot);
+                        }
+                    }
+                    Action::ToggleHud => main_window.hud.visible = !main_window.hud.visible,
+                    Action::HotbarSlot(slot) if mouse_captured => main_window.sandbox_window.select_slot(slot),
+                    Action::ToggleCapture => {
+                        // Show/hide cursor based on mouse capture state
+                        mouse_captured = !mouse_captured;
+                        sdl_context.mouse().set_relative_mouse_mode(mouse_captured);
+                    }
+                    // The prompt needs the cursor; running a command captures it again
+                    Action::OpenCommand => {
+                        mouse_captured = false;
+                        sdl_context.mouse().set_relative_mouse_mode(false);
+                        main_window.command_line.open();
+                    }
+                    _ => {}
+                }
+            }
+            
+            for action in released {
+                if action == Action::Break {
+                    break_held = false;
+                }
+                
+                // Letting go of the action that started a stroke commits it as one undo step
+                let finished = matches!(
+                    (action, stroke.as_ref().map(|s| s.action())),
+                    (Action::Break, Some(StrokeAction::Break)) | (Action::Place, Some(StrokeAction::Place(_)))
+                );
+                if finished {
+                    if let Some(finished) = stroke.take() {
+                        edit_history.push(finished.finish());
+                    }
+                }
+            }
+            
             match event {
                 Event::Quit { .. } => break 'running,
                 Event::Window{
@@ -560,112 +1449,27 @@ fn main() {
                     if mouse_captured {
                         let x_offset = xrel as f32;
                         let y_offset = -yrel as f32; // Inverted Y-axis
-                        camera.process_mouse_movement(x_offset, y_offset);
-                    }
-                    
-                    // Always pass mouse motion to egui for UI interaction
-                    egui_state.process_input(&window, event, &mut painter);
-                }
-                Event::MouseButtonDown { mouse_btn, .. } => {
-                    // Handle block placement/removal only if mouse is captured
-                    if mouse_captured {
-                        match mouse_btn {
-                            sdl2::mouse::MouseButton::Left => {
-                                // Ray cast and remove block
-                                let ray_dir = camera.front;
-                                let ray_pos = camera.position;
-                                let mut hit = false;
-                                let mut t = 0.0;
-                                
-                                // Use smaller steps for more precise hit detection
-                                while t < 10.0 && !hit {
-                                    let pos = ray_pos + ray_dir * t;
-                                    let block_x = pos.x.round() as i32;
-                                    let block_y = pos.y.round() as i32;
-                                    let block_z = pos.z.round() as i32;
-                                    
-                                    // Check if we're in a valid chunk
-                                    let chunk_x = (block_x as f32 / 16.0).floor() as i32;
-                                    let chunk_z = (block_z as f32 / 16.0).floor() as i32;
-                                    
-                                    if chunk_x >= -1 && chunk_x <= 1 && chunk_z >= -1 && chunk_z <= 1 {
-                                        if world.get_voxel(block_x, block_y, block_z) != VoxelType::Air {
-                                            // Remove block
-                                            world.set_voxel(block_x, block_y, block_z, VoxelType::Air);
-                                            hit = true;
-                                            println!("Removed block at ({}, {}, {})", block_x, block_y, block_z);
-                                        }
-                                    }
-                                    
-                                    t += 0.05; // Smaller step size for more precision
-                                }
-                            }
-                            sdl2::mouse::MouseButton::Right => {
-                                // Ray cast and place block
-                                let ray_dir = camera.front;
-                                let ray_pos = camera.position;
-                                let mut hit = false;
-                                let mut t = 0.0;
-                                let mut last_empty_pos = None;
-                                
-                                // Use smaller steps for more precise hit detection
-                                while t < 10.0 && !hit {
-                                    let pos = ray_pos + ray_dir * t;
-                                    let block_x = pos.x.round() as i32;
-                                    let block_y = pos.y.round() as i32;
-                                    let block_z = pos.z.round() as i32;
-                                    
-                                    // Check if we're in a valid chunk
-                                    let chunk_x = (block_x as f32 / 16.0).floor() as i32;
-                                    let chunk_z = (block_z as f32 / 16.0).floor() as i32;
-                                    
-                                    if chunk_x >= -1 && chunk_x <= 1 && chunk_z >= -1 && chunk_z <= 1 {
-                                        let current_voxel = world.get_voxel(block_x, block_y, block_z);
-                                        
-                                        if current_voxel != VoxelType::Air {
-                                            // If we found a solid block and have a previous empty position
-                                            if let Some((x, y, z)) = last_empty_pos {
-                                                // Place block at the last empty position
-                                                world.set_voxel(x, y, z, selected_block);
-                                                hit = true;
-                                                println!("Placed block at ({}, {}, {})", x, y, z);
-                                            }
-                                        } else {
-                                            // Store this empty position
-                                            last_empty_pos = Some((block_x, block_y, block_z));
-                                        }
-                                    }
-                                    
-                                    t += 0.05; // Smaller step size for more precision
-                                }
-                            }
-                            _ => {}
+                        if camera_rig.view == CameraView::Orbit {
+                            camera_rig.orbit_mouse(x_offset, y_offset, camera.mouse_sensitivity);
+                        } else {
+                            camera.process_mouse_movement(x_offset, y_offset);
                         }
                     }
                     
-                    // Always pass mouse button events to egui for UI interaction
+                    // Always pass mouse motion to egui for UI interaction
                     egui_state.process_input(&window, event, &mut painter);
                 }
-                Event::KeyDown { keycode, .. } => {
-                    // Handle ESC key to toggle mouse capture
-                    if let Some(key) = keycode {
-                        if key == Keycode::Escape {
-                            mouse_captured = !mouse_captured;
-                            
-                            // Show/hide cursor based on mouse capture state
-                            if mouse_captured {
-                                sdl_context.mouse().set_relative_mouse_mode(true);
-                            } else {
-                                sdl_context.mouse().set_relative_mouse_mode(false);
-                            }
-                        }
+                Event::MouseWheel { y, .. } if mouse_captured => {
+                    // Scrolling zooms while orbiting; otherwise scrolling up moves to the
+                    // previous slot, like most games
+                    if camera_rig.view == CameraView::Orbit {
+                        camera_rig.zoom(y);
+                    } else if y != 0 {
+                        main_window.sandbox_window.cycle_slot(-y.signum());
                     }
-                    
-                    // Pass key events to egui for UI interaction
-                    egui_state.process_input(&window, event, &mut painter);
                 }
                 _ => {
-                    // Pass other SDL2 events to egui for processing
+                    // Pass other SDL2 events (keys and mouse buttons included) to egui for processing
                         egui_state.process_input(&window, event, &mut painter);
                 }
                 }
@@ -743,28 +1547,34 @@ fn main() {
                         
                         // Set uniforms
                         let time_loc = gl::GetUniformLocation(compute_shader_program, CString::new("currentTime").unwrap().as_ptr());
-                        gl::Uniform1f(time_loc as GLint, timer);
+                        gl::Uniform1f(time_loc as GLint, frame_clock.elapsed());
                         
                         // Camera position
                         let cam_pos_loc = gl::GetUniformLocation(compute_shader_program, CString::new("cameraPosition").unwrap().as_ptr());
-                        gl::Uniform3f(cam_pos_loc as GLint, camera.position.x, camera.position.y, camera.position.z);
+                        gl::Uniform3f(cam_pos_loc as GLint, view_camera.position.x, view_camera.position.y, view_camera.position.z);
                         
                         // Camera direction
                         let cam_dir_loc = gl::GetUniformLocation(compute_shader_program, CString::new("cameraDirection").unwrap().as_ptr());
-                        gl::Uniform3f(cam_dir_loc as GLint, camera.front.x, camera.front.y, camera.front.z);
+                        gl::Uniform3f(cam_dir_loc as GLint, view_camera.front.x, view_camera.front.y, view_camera.front.z);
                         
                         // Camera up
                         let cam_up_loc = gl::GetUniformLocation(compute_shader_program, CString::new("cameraUp").unwrap().as_ptr());
-                        gl::Uniform3f(cam_up_loc as GLint, camera.up.x, camera.up.y, camera.up.z);
+                        gl::Uniform3f(cam_up_loc as GLint, view_camera.up.x, view_camera.up.y, view_camera.up.z);
                         
                         // Camera right
                         let cam_right_loc = gl::GetUniformLocation(compute_shader_program, CString::new("cameraRight").unwrap().as_ptr());
-                        gl::Uniform3f(cam_right_loc as GLint, camera.right.x, camera.right.y, camera.right.z);
+                        gl::Uniform3f(cam_right_loc as GLint, view_camera.right.x, view_camera.right.y, view_camera.right.z);
                         
                         // Screen resolution
                         let screen_res_loc = gl::GetUniformLocation(compute_shader_program, CString::new("screenResolution").unwrap().as_ptr());
                         gl::Uniform2f(screen_res_loc as GLint, SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32);
                         
+                        // View and projection matrices, plus their inverses for generating rays
+                        set_matrix_uniform(compute_shader_program, "viewMatrix", &view_camera.get_view_matrix());
+                        set_matrix_uniform(compute_shader_program, "projectionMatrix", &view_camera.get_projection_matrix());
+                        set_matrix_uniform(compute_shader_program, "inverseView", &glm::inverse(&view_camera.get_view_matrix()));
+                        set_matrix_uniform(compute_shader_program, "inverseProjection", &glm::inverse(&view_camera.get_projection_matrix()));
+                        
                         // World size
                         let world_size_loc = gl::GetUniformLocation(compute_shader_program, CString::new("worldSize").unwrap().as_ptr());
                         gl::Uniform3i(world_size_loc as GLint, 3, 1, 3);  // 3x1x3 chunks
@@ -805,3 +1615,210 @@ fn create_texture(width: u32, height: u32) -> GLuint {
     }
     texture
 }
+
+// Upload a matrix to a mat4 uniform; glm stores columns, which is the layout GL expects
+fn set_matrix_uniform(program: GLuint, name: &str, matrix: &glm::Mat4) {
+    let mut values = [0.0f32; 16];
+    for column in 0..4 {
+        for row in 0..4 {
+            values[column * 4 + row] = matrix[column][row];
+        }
+    }
+    unsafe {
+        let location = gl::GetUniformLocation(program, CString::new(name).unwrap().as_ptr());
+        gl::UniformMatrix4fv(location, 1, gl::FALSE, values.as_ptr());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world_with(blocks: &[((i32, i32, i32), VoxelType)]) -> World {
+        let mut world = World::new();
+        for &((x, y, z), voxel_type) in blocks {
+            world.set_voxel(x, y, z, voxel_type);
+        }
+        world
+    }
+
+    #[test]
+    fn edited_chunks_still_wait_for_their_terrain() {
+        let mut world = world_with(&[((3, 2, 1), VoxelType::Wood)]);
+        assert!(!world.has_chunk((0, 0, 0)));
+
+        world.insert_chunk(Chunk::new((0, 0, 0)));
+        assert!(world.has_chunk((0, 0, 0)));
+        assert_eq!(world.get_voxel(3, 2, 1), VoxelType::Wood);
+    }
+
+    #[test]
+    fn raycast_axis_aligned_positive_x() {
+        let world = world_with(&[((5, 0, 0), VoxelType::Stone)]);
+        let hit = world.raycast(vec3(0.5, 0.5, 0.5), vec3(1.0, 0.0, 0.0), 10.0, false).unwrap();
+        assert_eq!(hit.voxel, (5, 0, 0));
+        assert_eq!(hit.normal, (-1, 0, 0));
+        assert_eq!(hit.place, (4, 0, 0));
+        assert!((hit.distance - 4.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn raycast_axis_aligned_negative_y_across_chunks() {
+        let world = world_with(&[((0, -3, 0), VoxelType::Dirt)]);
+        let hit = world.raycast(vec3(0.5, 2.5, 0.5), vec3(0.0, -1.0, 0.0), 10.0, false).unwrap();
+        assert_eq!(hit.voxel, (0, -3, 0));
+        assert_eq!(hit.voxel_type, VoxelType::Dirt);
+        assert_eq!(hit.normal, (0, 1, 0));
+        assert_eq!(hit.place, (0, -2, 0));
+        assert!((hit.distance - 4.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn raycast_diagonal_enters_through_side_face() {
+        let world = world_with(&[((4, 2, 0), VoxelType::Stone)]);
+        let hit = world.raycast(vec3(0.2, 0.5, 0.5), vec3(1.0, 0.5, 0.0), 10.0, false).unwrap();
+        assert_eq!(hit.voxel, (4, 2, 0));
+        assert_eq!(hit.normal, (-1, 0, 0));
+        assert_eq!(hit.place, (3, 2, 0));
+        assert!((hit.distance - 3.8 * 1.25f32.sqrt()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn raycast_diagonal_catches_grazed_corner() {
+        // The ray only clips ~0.01 blocks of the corner, which fixed-step sampling skips
+        let world = world_with(&[((1, 0, 0), VoxelType::Stone), ((2, 2, 0), VoxelType::Stone)]);
+        let hit = world.raycast(vec3(0.5, 0.5, 0.5), vec3(1.0, 0.98, 0.0), 10.0, false).unwrap();
+        assert_eq!(hit.voxel, (1, 0, 0));
+        assert_eq!(hit.normal, (-1, 0, 0));
+    }
+
+    #[test]
+    fn raycast_diagonal_from_above_hits_top_face() {
+        let world = world_with(&[((3, 0, 3), VoxelType::Grass)]);
+        let hit = world.raycast(vec3(0.5, 3.9, 0.5), vec3(1.0, -1.0, 1.0), 10.0, false).unwrap();
+        assert_eq!(hit.voxel, (3, 0, 3));
+        assert_eq!(hit.normal, (0, 1, 0));
+        assert_eq!(hit.place, (3, 1, 3));
+    }
+
+    #[test]
+    fn raycast_respects_max_distance() {
+        let world = world_with(&[((8, 0, 0), VoxelType::Stone)]);
+        assert!(world.raycast(vec3(0.5, 0.5, 0.5), vec3(1.0, 0.0, 0.0), 5.0, false).is_none());
+        assert!(world.raycast(vec3(0.5, 0.5, 0.5), vec3(-1.0, 0.0, 0.0), 10.0, false).is_none());
+    }
+
+    #[test]
+    fn raycast_passes_through_or_targets_liquids() {
+        let world = world_with(&[((2, 0, 0), VoxelType::Water), ((3, 0, 0), VoxelType::Stone)]);
+        let through = world.raycast(vec3(0.5, 0.5, 0.5), vec3(1.0, 0.0, 0.0), 10.0, false).unwrap();
+        assert_eq!(through.voxel, (3, 0, 0));
+        assert_eq!(through.place, (2, 0, 0));
+
+        let targeted = world.raycast(vec3(0.5, 0.5, 0.5), vec3(1.0, 0.0, 0.0), 10.0, true).unwrap();
+        assert_eq!(targeted.voxel, (2, 0, 0));
+        assert_eq!(targeted.voxel_type, VoxelType::Water);
+    }
+
+    #[test]
+    fn raycast_starting_inside_a_block() {
+        let world = world_with(&[((0, 0, 0), VoxelType::Stone)]);
+        let hit = world.raycast(vec3(0.5, 0.5, 0.5), vec3(0.0, 0.0, 1.0), 10.0, false).unwrap();
+        assert_eq!(hit.voxel, (0, 0, 0));
+        assert_eq!(hit.normal, (0, 0, 0));
+        assert_eq!(hit.distance, 0.0);
+    }
+
+    // Camera looking along -Z and pitched 45 degrees down, with no easing so speeds are exact
+    fn test_camera(mode: MovementMode) -> Camera {
+        let mut camera = Camera::new();
+        camera.position = vec3(0.0, 0.0, 0.0);
+        camera.movement_mode = mode;
+        camera.acceleration = 1.0e6;
+        camera.damping = 1.0e6;
+        camera.yaw = -90.0;
+        camera.pitch = 0.0;
+        camera.process_mouse_movement(0.0, -45.0 / camera.mouse_sensitivity);
+        camera
+    }
+
+    #[test]
+    fn opposing_keys_cancel() {
+        let intent = MovementIntent::from_keys(true, true, true, false, false, true);
+        assert_eq!(intent, MovementIntent { forward: 0.0, right: -1.0, up: -1.0 });
+        assert!(MovementIntent::from_keys(false, false, false, false, false, false).is_idle());
+    }
+
+    #[test]
+    fn diagonal_movement_is_not_faster() {
+        let mut straight = test_camera(MovementMode::Walk);
+        let mut diagonal = test_camera(MovementMode::Walk);
+        straight.apply_movement(MovementIntent { forward: 1.0, ..Default::default() }, 1.0);
+        diagonal.apply_movement(MovementIntent { forward: 1.0, right: 1.0, up: 1.0 }, 1.0);
+        assert!((glm::length(straight.velocity) - straight.movement_speed).abs() < 1e-4);
+        assert!((glm::length(diagonal.velocity) - diagonal.movement_speed).abs() < 1e-4);
+    }
+
+    #[test]
+    fn walking_stays_level() {
+        let mut camera = test_camera(MovementMode::Walk);
+        camera.apply_movement(MovementIntent { forward: 1.0, ..Default::default() }, 1.0);
+        assert!(camera.position.y.abs() < 1e-4);
+        assert!((camera.position.z + camera.movement_speed).abs() < 1e-4);
+    }
+
+    #[test]
+    fn flying_follows_the_view_direction() {
+        let mut camera = test_camera(MovementMode::Fly);
+        camera.apply_movement(MovementIntent { forward: 1.0, ..Default::default() }, 1.0);
+        let expected = camera.movement_speed / 2f32.sqrt();
+        assert!((camera.position.y + expected).abs() < 1e-3);
+        assert!((camera.position.z + expected).abs() < 1e-3);
+    }
+
+    #[test]
+    fn acceleration_and_damping_ease_velocity() {
+        let mut camera = test_camera(MovementMode::Walk);
+        camera.acceleration = 10.0;
+        camera.damping = 5.0;
+        let forward = MovementIntent { forward: 1.0, ..Default::default() };
+
+        camera.apply_movement(forward, 0.05);
+        let first = glm::length(camera.velocity);
+        assert!(first > 0.0 && first < camera.movement_speed);
+        for _ in 0..200 {
+            camera.apply_movement(forward, 0.05);
+        }
+        assert!((glm::length(camera.velocity) - camera.movement_speed).abs() < 1e-3);
+
+        camera.apply_movement(MovementIntent::default(), 0.05);
+        let slowing = glm::length(camera.velocity);
+        assert!(slowing > 0.0 && slowing < camera.movement_speed);
+        for _ in 0..200 {
+            camera.apply_movement(MovementIntent::default(), 0.05);
+        }
+        assert!(glm::length(camera.velocity) < 1e-3);
+    }
+
+    #[test]
+    fn picking_ray_goes_through_the_crosshair() {
+        let mut camera = test_camera(MovementMode::Fly);
+        camera.process_mouse_movement(200.0, -150.0);
+        let pick = camera.pick_direction();
+        assert!(glm::length(pick - camera.front) < 1e-4);
+    }
+
+    #[test]
+    fn screen_edge_rays_follow_the_field_of_view() {
+        let mut camera = test_camera(MovementMode::Fly);
+        camera.fov = 90.0;
+        camera.aspect = 2.0;
+        let angle = |v: Vec3| glm::dot(v, camera.front).acos().to_degrees();
+        assert!((angle(camera.ray_direction(0.0, 1.0)) - 45.0).abs() < 1e-2);
+        // Horizontal half-angle is atan(aspect * tan(fov / 2))
+        let horizontal = 2.0f32.atan().to_degrees();
+        assert!((angle(camera.ray_direction(1.0, 0.0)) - horizontal).abs() < 1e-2);
+        assert!(glm::dot(camera.ray_direction(1.0, 0.0), camera.right) > 0.0);
+        assert!(glm::dot(camera.ray_direction(0.0, 1.0), camera.up) > 0.0);
+    }
+}
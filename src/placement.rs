@@ -0,0 +1,209 @@
+use std::fmt;
+
+use crate::{VoxelType, World};
+
+// Player collision box around the camera (the camera sits at eye height)
+pub const PLAYER_HALF_WIDTH: f32 = 0.3;
+pub const PLAYER_HEIGHT: f32 = 1.8;
+pub const PLAYER_EYE_HEIGHT: f32 = 1.6;
+
+// Axis-aligned bounding box in world units
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: (f32, f32, f32),
+    pub max: (f32, f32, f32),
+}
+
+impl Aabb {
+    // Box of a player whose eyes are at `eye`
+    pub fn player(eye: (f32, f32, f32)) -> Self {
+        Self {
+            min: (eye.0 - PLAYER_HALF_WIDTH, eye.1 - PLAYER_EYE_HEIGHT, eye.2 - PLAYER_HALF_WIDTH),
+            max: (eye.0 + PLAYER_HALF_WIDTH, eye.1 + PLAYER_HEIGHT - PLAYER_EYE_HEIGHT, eye.2 + PLAYER_HALF_WIDTH),
+        }
+    }
+
+    // Whether the box overlaps the block occupying [cell, cell + 1]
+    pub fn intersects_block(&self, cell: (i32, i32, i32)) -> bool {
+        let (x, y, z) = (cell.0 as f32, cell.1 as f32, cell.2 as f32);
+        self.min.0 < x + 1.0 && self.max.0 > x
+            && self.min.1 < y + 1.0 && self.max.1 > y
+            && self.min.2 < z + 1.0 && self.max.2 > z
+    }
+}
+
+// What a block needs next to it before it can be placed
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Support {
+    None,      // Can float freely
+    Adjacent,  // Needs a solid block on any side
+    Below,     // Needs a solid block underneath
+}
+
+pub fn support_requirement(voxel_type: VoxelType) -> Support {
+    match voxel_type {
+        VoxelType::Air | VoxelType::Water | VoxelType::Leaves => Support::None,
+        VoxelType::Grass => Support::Below,
+        _ => Support::Adjacent,
+    }
+}
+
+// Limits checked before a block is placed
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PlacementRules {
+    pub min_y: i32,
+    pub max_y: i32,
+    pub border_min: i32, // Lowest allowed X and Z
+    pub border_max: i32, // Highest allowed X and Z
+    pub require_support: bool,
+}
+
+impl PlacementRules {
+    // Defaults cover exactly the loaded 3x3 chunk area
+    pub fn new() -> Self {
+        Self {
+            min_y: 0,
+            max_y: 15,
+            border_min: -16,
+            border_max: 31,
+            require_support: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PlacementError {
+    TooHigh,
+    TooLow,
+    OutsideBorder,
+    Occupied,
+    OverlapsPlayer,
+    OverlapsEntity,
+    NeedsSupport,
+    NeedsGround,
+}
+
+impl fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            PlacementError::TooHigh => "Can't place above the height limit",
+            PlacementError::TooLow => "Can't place below the bottom of the world",
+            PlacementError::OutsideBorder => "Can't place outside the world border",
+            PlacementError::Occupied => "That space is already taken",
+            PlacementError::OverlapsPlayer => "Can't place a block inside yourself",
+            PlacementError::OverlapsEntity => "Something is in the way",
+            PlacementError::NeedsSupport => "That block needs something to attach to",
+            PlacementError::NeedsGround => "That block needs solid ground underneath",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+// Check whether `voxel_type` may go at `cell`. The player box comes first in `blockers`,
+// followed by any entities.
+pub fn validate(
+    world: &World,
+    rules: &PlacementRules,
+    cell: (i32, i32, i32),
+    voxel_type: VoxelType,
+    blockers: &[Aabb],
+) -> Result<(), PlacementError> {
+    let (x, y, z) = cell;
+    if y > rules.max_y {
+        return Err(PlacementError::TooHigh);
+    }
+    if y < rules.min_y {
+        return Err(PlacementError::TooLow);
+    }
+    let in_border = |v: i32| v >= rules.border_min && v <= rules.border_max;
+    if !in_border(x) || !in_border(z) {
+        return Err(PlacementError::OutsideBorder);
+    }
+    if world.get_voxel(x, y, z).is_solid() {
+        return Err(PlacementError::Occupied);
+    }
+
+    // Liquids flow around bodies, so only solid blocks are blocked by them
+    if voxel_type.is_solid() {
+        if let Some(i) = blockers.iter().position(|aabb| aabb.intersects_block(cell)) {
+            return Err(if i == 0 { PlacementError::OverlapsPlayer } else { PlacementError::OverlapsEntity });
+        }
+    }
+
+    if rules.require_support {
+        let solid = |dx: i32, dy: i32, dz: i32| world.get_voxel(x + dx, y + dy, z + dz).is_solid();
+        match support_requirement(voxel_type) {
+            Support::None => {}
+            Support::Below => {
+                if !solid(0, -1, 0) {
+                    return Err(PlacementError::NeedsGround);
+                }
+            }
+            Support::Adjacent => {
+                let attached = solid(1, 0, 0) || solid(-1, 0, 0)
+                    || solid(0, 1, 0) || solid(0, -1, 0)
+                    || solid(0, 0, 1) || solid(0, 0, -1);
+                if !attached {
+                    return Err(PlacementError::NeedsSupport);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_cells_outside_the_limits() {
+        let world = World::new();
+        let rules = PlacementRules { require_support: false, ..PlacementRules::new() };
+        let place = |cell| validate(&world, &rules, cell, VoxelType::Stone, &[]);
+        assert_eq!(place((0, 16, 0)), Err(PlacementError::TooHigh));
+        assert_eq!(place((0, -1, 0)), Err(PlacementError::TooLow));
+        assert_eq!(place((32, 5, 0)), Err(PlacementError::OutsideBorder));
+        assert_eq!(place((0, 5, -17)), Err(PlacementError::OutsideBorder));
+        assert_eq!(place((31, 15, -16)), Ok(()));
+    }
+
+    #[test]
+    fn rejects_occupied_cells_and_bodies() {
+        let mut world = World::new();
+        world.set_voxel(0, 0, 0, VoxelType::Stone);
+        let rules = PlacementRules::new();
+        assert_eq!(validate(&world, &rules, (0, 0, 0), VoxelType::Wood, &[]), Err(PlacementError::Occupied));
+
+        // The player stands on top of the stone, so their feet fill the cell above it
+        let player = Aabb::player((0.5, 1.0 + PLAYER_EYE_HEIGHT, 0.5));
+        let entity = Aabb::player((1.5, 1.0 + PLAYER_EYE_HEIGHT, 0.5));
+        let far_away = Aabb::player((10.5, 1.0 + PLAYER_EYE_HEIGHT, 10.5));
+        assert_eq!(validate(&world, &rules, (0, 1, 0), VoxelType::Wood, &[player]), Err(PlacementError::OverlapsPlayer));
+        assert_eq!(
+            validate(&world, &rules, (1, 1, 0), VoxelType::Wood, &[far_away, entity]),
+            Err(PlacementError::OverlapsEntity)
+        );
+        assert_eq!(validate(&world, &rules, (0, 1, 0), VoxelType::Wood, &[far_away]), Ok(()));
+        // Water flows around the player instead
+        assert_eq!(validate(&world, &rules, (0, 1, 0), VoxelType::Water, &[player]), Ok(()));
+    }
+
+    #[test]
+    fn support_depends_on_the_block() {
+        let mut world = World::new();
+        world.set_voxel(0, 5, 0, VoxelType::Stone);
+        let rules = PlacementRules::new();
+        let place = |cell, voxel_type| validate(&world, &rules, cell, voxel_type, &[]);
+        assert_eq!(place((5, 5, 5), VoxelType::Wood), Err(PlacementError::NeedsSupport));
+        assert_eq!(place((1, 5, 0), VoxelType::Wood), Ok(()));
+        assert_eq!(place((0, 4, 0), VoxelType::Wood), Ok(()));
+        assert_eq!(place((5, 5, 5), VoxelType::Leaves), Ok(()));
+        // Grass only grows on top of something
+        assert_eq!(place((1, 5, 0), VoxelType::Grass), Err(PlacementError::NeedsGround));
+        assert_eq!(place((0, 6, 0), VoxelType::Grass), Ok(()));
+
+        let free = PlacementRules { require_support: false, ..rules };
+        assert_eq!(validate(&world, &free, (5, 5, 5), VoxelType::Grass, &[]), Ok(()));
+    }
+}
@@ -0,0 +1,376 @@
+use crate::placement::{Aabb, PLAYER_EYE_HEIGHT, PLAYER_HALF_WIDTH, PLAYER_HEIGHT};
+use crate::World;
+
+// Downward acceleration in blocks per second squared
+pub const GRAVITY: f32 = 28.0;
+// Upward speed given by a jump; enough to clear a single block
+pub const JUMP_SPEED: f32 = 9.0;
+const TERMINAL_VELOCITY: f32 = 50.0;
+// Tallest ledge walked onto without jumping
+pub const STEP_HEIGHT: f32 = 1.0;
+// Margin keeping boxes that exactly touch a block face from counting as overlapping it
+const EPSILON: f32 = 1e-4;
+// Speed multipliers applied to walking
+pub const SPRINT_SPEED: f32 = 1.5;
+pub const CROUCH_SPEED: f32 = 0.4;
+// Eye height while crouching, and how quickly the eye moves between heights (per second)
+pub const CROUCH_EYE_HEIGHT: f32 = 1.25;
+const EYE_HEIGHT_RATE: f32 = 12.0;
+// A crouching player won't move anywhere that would leave more than this much air under them
+const SNEAK_DROP: f32 = 0.6;
+// Step by which sneaking shortens a move that would walk off an edge
+const SNEAK_BACKOFF: f32 = 0.02;
+
+// Walking body with gravity, colliding against solid voxels
+pub struct Player {
+    pub position: (f32, f32, f32), // Center of the feet
+    pub velocity: (f32, f32, f32),
+    pub on_ground: bool,
+    pub crouching: bool, // Lowers the eye and keeps the player from walking off edges
+    eye_height: f32,     // Current eye height above the feet, easing towards the stance's
+}
+
+impl Player {
+    pub fn new(position: (f32, f32, f32)) -> Self {
+        Self {
+            position,
+            velocity: (0.0, 0.0, 0.0),
+            on_ground: false,
+            crouching: false,
+            eye_height: PLAYER_EYE_HEIGHT,
+        }
+    }
+
+    pub fn from_eye(eye: (f32, f32, f32)) -> Self {
+        Self::new((eye.0, eye.1 - PLAYER_EYE_HEIGHT, eye.2))
+    }
+
+    pub fn eye(&self) -> (f32, f32, f32) {
+        (self.position.0, self.position.1 + self.eye_height, self.position.2)
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        let (x, y, z) = self.position;
+        Aabb {
+            min: (x - PLAYER_HALF_WIDTH, y, z - PLAYER_HALF_WIDTH),
+            max: (x + PLAYER_HALF_WIDTH, y + PLAYER_HEIGHT, z + PLAYER_HALF_WIDTH),
+        }
+    }
+
+    // Advance one tick. `walk` is the wanted horizontal velocity (X, Z); jumping only
+    // works while standing on something.
+    pub fn tick(&mut self, world: &World, walk: (f32, f32), jump: bool, delta_time: f32) {
+        let target_eye = if self.crouching { CROUCH_EYE_HEIGHT } else { PLAYER_EYE_HEIGHT };
+        self.eye_height += (target_eye - self.eye_height) * (1.0 - (-EYE_HEIGHT_RATE * delta_time).exp());
+
+        self.velocity.0 = walk.0;
+        self.velocity.2 = walk.1;
+        if jump && self.on_ground {
+            self.velocity.1 = JUMP_SPEED;
+        }
+        self.velocity.1 = (self.velocity.1 - GRAVITY * delta_time).max(-TERMINAL_VELOCITY);
+
+        let was_on_ground = self.on_ground;
+
+        // Vertical first, so landing is known before sliding along walls
+        let dy = self.velocity.1 * delta_time;
+        let moved = sweep(world, &self.aabb(), 1, dy);
+        self.position.1 += moved;
+        self.on_ground = dy < 0.0 && moved > dy;
+        if moved != dy {
+            self.velocity.1 = 0.0;
+        }
+
+        for axis in [0, 2] {
+            let mut wanted = if axis == 0 { self.velocity.0 } else { self.velocity.2 } * delta_time;
+            // Sneaking along an edge: shorten the move until there's still ground underneath
+            if self.crouching && self.on_ground {
+                while wanted != 0.0 && !self.supported_after(world, axis, wanted) {
+                    wanted = if wanted.abs() <= SNEAK_BACKOFF { 0.0 } else { wanted - SNEAK_BACKOFF * wanted.signum() };
+                }
+            }
+            if wanted == 0.0 {
+                continue;
+            }
+            let moved = sweep(world, &self.aabb(), axis, wanted);
+            if moved == wanted {
+                self.shift(axis, moved);
+                continue;
+            }
+
+            // Blocked: try again from one step higher, then settle back down onto the ledge
+            if was_on_ground || self.on_ground {
+                let raise = sweep(world, &self.aabb(), 1, STEP_HEIGHT);
+                let raised = offset(&self.aabb(), 1, raise);
+                let raised_moved = sweep(world, &raised, axis, wanted);
+                if raised_moved.abs() > moved.abs() + EPSILON {
+                    self.position.1 += raise;
+                    self.shift(axis, raised_moved);
+                    self.position.1 += sweep(world, &self.aabb(), 1, -raise);
+                    continue;
+                }
+            }
+            self.shift(axis, moved);
+            if axis == 0 {
+                self.velocity.0 = 0.0;
+            } else {
+                self.velocity.2 = 0.0;
+            }
+        }
+    }
+
+    // Whether moving `amount` along `axis` would leave ground within SNEAK_DROP below
+    fn supported_after(&self, world: &World, axis: usize, amount: f32) -> bool {
+        let moved = offset(&self.aabb(), axis, amount);
+        sweep(world, &moved, 1, -SNEAK_DROP) > -SNEAK_DROP
+    }
+
+    fn shift(&mut self, axis: usize, amount: f32) {
+        match axis {
+            0 => self.position.0 += amount,
+            1 => self.position.1 += amount,
+            _ => self.position.2 += amount,
+        }
+    }
+}
+
+fn component(v: (f32, f32, f32), axis: usize) -> f32 {
+    match axis {
+        0 => v.0,
+        1 => v.1,
+        _ => v.2,
+    }
+}
+
+fn offset(aabb: &Aabb, axis: usize, amount: f32) -> Aabb {
+    let mut moved = *aabb;
+    match axis {
+        0 => { moved.min.0 += amount; moved.max.0 += amount; }
+        1 => { moved.min.1 += amount; moved.max.1 += amount; }
+        _ => { moved.min.2 += amount; moved.max.2 += amount; }
+    }
+    moved
+}
+
+// How far `aabb` can move along `axis` (up to `distance`, either sign) before touching a solid
+// block. Blocks the box already overlaps are ignored so it can't get stuck inside terrain.
+fn sweep(world: &World, aabb: &Aabb, axis: usize, distance: f32) -> f32 {
+    if distance == 0.0 {
+        return 0.0;
+    }
+    let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+    let cells = |axis: usize| {
+        let low = (component(aabb.min, axis) + EPSILON).floor() as i32;
+        let high = (component(aabb.max, axis) - EPSILON).floor() as i32;
+        low..=high
+    };
+    let blocked = |layer: i32| {
+        cells(a).any(|i| {
+            cells(b).any(|j| {
+                let mut cell = [0; 3];
+                cell[axis] = layer;
+                cell[a] = i;
+                cell[b] = j;
+                world.get_voxel(cell[0], cell[1], cell[2]).is_solid()
+            })
+        })
+    };
+
+    if distance > 0.0 {
+        let front = component(aabb.max, axis);
+        let first = (front - EPSILON).ceil() as i32;
+        let last = (front + distance).floor() as i32;
+        for layer in first..=last {
+            if blocked(layer) {
+                return (layer as f32 - front).max(0.0).min(distance);
+            }
+        }
+    } else {
+        let front = component(aabb.min, axis);
+        let first = (front + EPSILON).floor() as i32 - 1;
+        let last = (front + distance).floor() as i32;
+        for layer in (last..=first).rev() {
+            if blocked(layer) {
+                return ((layer + 1) as f32 - front).min(0.0).max(distance);
+            }
+        }
+    }
+    distance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VoxelType;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    // Stone floor whose top is at y = 1, covering x and z from -4 to 4
+    fn floor_world() -> World {
+        let mut world = World::new();
+        for x in -4..=4 {
+            for z in -4..=4 {
+                world.set_voxel(x, 0, z, VoxelType::Stone);
+            }
+        }
+        world
+    }
+
+    fn run(player: &mut Player, world: &World, walk: (f32, f32), ticks: usize) {
+        for _ in 0..ticks {
+            player.tick(world, walk, false, DT);
+        }
+    }
+
+    #[test]
+    fn falls_and_lands_on_the_floor() {
+        let world = floor_world();
+        let mut player = Player::new((0.5, 5.0, 0.5));
+        run(&mut player, &world, (0.0, 0.0), 120);
+        assert!((player.position.1 - 1.0).abs() < 1e-4);
+        assert!(player.on_ground);
+        assert_eq!(player.velocity.1, 0.0);
+    }
+
+    #[test]
+    fn jumps_only_from_the_ground() {
+        let world = floor_world();
+        let mut player = Player::new((0.5, 1.0, 0.5));
+        run(&mut player, &world, (0.0, 0.0), 2);
+        player.tick(&world, (0.0, 0.0), true, DT);
+        assert!(player.position.1 > 1.0 && !player.on_ground);
+
+        // A second jump in mid-air does nothing
+        let rising = player.velocity.1;
+        player.tick(&world, (0.0, 0.0), true, DT);
+        assert!(player.velocity.1 < rising);
+
+        run(&mut player, &world, (0.0, 0.0), 120);
+        assert!((player.position.1 - 1.0).abs() < 1e-4 && player.on_ground);
+    }
+
+    #[test]
+    fn slides_along_a_wall() {
+        let mut world = floor_world();
+        for z in -4..=4 {
+            for y in 1..=3 {
+                world.set_voxel(2, y, z, VoxelType::Stone);
+            }
+        }
+        let mut player = Player::new((0.5, 1.0, 0.5));
+        run(&mut player, &world, (3.0, 3.0), 40);
+        assert!((player.position.0 - (2.0 - PLAYER_HALF_WIDTH)).abs() < 1e-4);
+        assert!(player.position.2 > 2.4);
+    }
+
+    #[test]
+    fn lands_on_a_block_edge() {
+        let mut world = World::new();
+        world.set_voxel(0, 0, 0, VoxelType::Stone);
+        // Only 0.05 of the box overlaps the block
+        let mut player = Player::new((1.25, 3.0, 0.5));
+        run(&mut player, &world, (0.0, 0.0), 120);
+        assert!((player.position.1 - 1.0).abs() < 1e-4);
+        assert!(player.on_ground);
+    }
+
+    #[test]
+    fn falls_past_an_edge_it_only_touches() {
+        let mut world = World::new();
+        world.set_voxel(0, 0, 0, VoxelType::Stone);
+        let mut player = Player::new((1.0 + PLAYER_HALF_WIDTH, 3.0, 0.5));
+        run(&mut player, &world, (0.0, 0.0), 60);
+        assert!(player.position.1 < 1.0);
+    }
+
+    #[test]
+    fn steps_onto_a_single_block() {
+        let mut world = floor_world();
+        for z in -4..=4 {
+            world.set_voxel(2, 1, z, VoxelType::Stone);
+        }
+        let mut player = Player::new((0.5, 1.0, 0.5));
+        run(&mut player, &world, (0.0, 0.0), 2);
+        run(&mut player, &world, (3.0, 0.0), 40);
+        assert!((player.position.1 - 2.0).abs() < 1e-4);
+        assert!(player.position.0 > 2.0);
+    }
+
+    #[test]
+    fn does_not_step_up_a_two_block_wall() {
+        let mut world = floor_world();
+        for z in -4..=4 {
+            world.set_voxel(2, 1, z, VoxelType::Stone);
+            world.set_voxel(2, 2, z, VoxelType::Stone);
+        }
+        let mut player = Player::new((0.5, 1.0, 0.5));
+        run(&mut player, &world, (0.0, 0.0), 2);
+        run(&mut player, &world, (3.0, 0.0), 40);
+        assert!((player.position.1 - 1.0).abs() < 1e-4);
+        assert!((player.position.0 - (2.0 - PLAYER_HALF_WIDTH)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn ceiling_stops_a_jump() {
+        let mut world = floor_world();
+        world.set_voxel(0, 3, 0, VoxelType::Stone);
+        let mut player = Player::new((0.5, 1.0, 0.5));
+        run(&mut player, &world, (0.0, 0.0), 2);
+        player.tick(&world, (0.0, 0.0), true, DT);
+        for _ in 0..10 {
+            player.tick(&world, (0.0, 0.0), false, DT);
+            assert!(player.aabb().max.1 <= 3.0 + 1e-4);
+        }
+    }
+
+    #[test]
+    fn crouching_stops_at_the_edge() {
+        let world = floor_world();
+        let mut player = Player::new((0.5, 1.0, 0.5));
+        player.crouching = true;
+        run(&mut player, &world, (0.0, 0.0), 2);
+        run(&mut player, &world, (3.0, 0.0), 120);
+        assert!(player.on_ground && (player.position.1 - 1.0).abs() < 1e-4);
+        // The floor ends at x = 5; the box may hang over it but not leave it
+        assert!(player.position.0 > 5.0 - PLAYER_HALF_WIDTH - 0.05);
+        assert!(player.position.0 < 5.0 + PLAYER_HALF_WIDTH);
+
+        player.crouching = false;
+        run(&mut player, &world, (3.0, 0.0), 60);
+        assert!(player.position.1 < 1.0);
+    }
+
+    #[test]
+    fn crouching_still_slides_along_an_edge() {
+        let world = floor_world();
+        let mut player = Player::new((4.5, 1.0, 0.5));
+        player.crouching = true;
+        run(&mut player, &world, (0.0, 0.0), 2);
+        run(&mut player, &world, (3.0, 3.0), 60);
+        assert!(player.position.0 < 5.0 + PLAYER_HALF_WIDTH);
+        assert!(player.position.2 > 2.0);
+        assert!((player.position.1 - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn eye_height_eases_between_stances() {
+        let world = floor_world();
+        let mut player = Player::new((0.5, 1.0, 0.5));
+        player.crouching = true;
+        player.tick(&world, (0.0, 0.0), false, DT);
+        let lowering = player.eye().1 - player.position.1;
+        assert!(lowering < PLAYER_EYE_HEIGHT && lowering > CROUCH_EYE_HEIGHT);
+        run(&mut player, &world, (0.0, 0.0), 60);
+        assert!((player.eye().1 - player.position.1 - CROUCH_EYE_HEIGHT).abs() < 1e-3);
+    }
+
+    #[test]
+    fn walks_on_through_liquids() {
+        let mut world = floor_world();
+        world.set_voxel(1, 1, 0, VoxelType::Water);
+        let mut player = Player::new((0.5, 1.0, 0.5));
+        run(&mut player, &world, (3.0, 0.0), 30);
+        assert!(player.position.0 > 1.8);
+        assert!((player.position.1 - 1.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,262 @@
+// Parametric shapes for the drawing tools, as lists of voxel cells
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ShapeKind {
+    Line,      // Between the two anchors
+    Plane,     // Rectangle spanned by the two anchors, flattened along its thinnest side
+    Sphere,    // Centered on the first anchor
+    Ellipsoid, // Centered on the first anchor
+    Cylinder,  // Upright, standing on the first anchor
+    Torus,     // Lying flat, centered on the first anchor
+}
+
+impl ShapeKind {
+    pub const ALL: [ShapeKind; 6] = [
+        ShapeKind::Line,
+        ShapeKind::Plane,
+        ShapeKind::Sphere,
+        ShapeKind::Ellipsoid,
+        ShapeKind::Cylinder,
+        ShapeKind::Torus,
+    ];
+
+    pub fn anchor_count(self) -> usize {
+        match self {
+            ShapeKind::Line | ShapeKind::Plane => 2,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ShapeParams {
+    pub kind: ShapeKind,
+    pub hollow: bool,
+    pub radius: i32,              // Sphere, cylinder and torus (ring) radius
+    pub radii: (i32, i32, i32),   // Ellipsoid radii along X, Y and Z
+    pub height: i32,              // Cylinder height
+    pub minor_radius: i32,        // Thickness of the torus tube
+}
+
+impl ShapeParams {
+    pub fn new() -> Self {
+        Self {
+            kind: ShapeKind::Sphere,
+            hollow: false,
+            radius: 4,
+            radii: (6, 3, 4),
+            height: 5,
+            minor_radius: 1,
+        }
+    }
+}
+
+// Cells of the shape, or nothing if it still needs another anchor
+pub fn shape_cells(params: &ShapeParams, first: (i32, i32, i32), second: Option<(i32, i32, i32)>) -> Vec<(i32, i32, i32)> {
+    let round = |r: i32| (r as f32 + 0.5) * (r as f32 + 0.5);
+    match params.kind {
+        ShapeKind::Line => match second {
+            Some(second) => line_cells(first, second),
+            None => Vec::new(),
+        },
+        ShapeKind::Plane => match second {
+            Some(second) => plane_cells(first, second, params.hollow),
+            None => Vec::new(),
+        },
+        ShapeKind::Sphere => {
+            let r = params.radius;
+            let limit = round(r);
+            volume_cells(first, (r, r, r), params.hollow, |x, y, z| ((x * x + y * y + z * z) as f32) <= limit)
+        }
+        ShapeKind::Ellipsoid => {
+            let (rx, ry, rz) = params.radii;
+            let (ex, ey, ez) = (rx as f32 + 0.5, ry as f32 + 0.5, rz as f32 + 0.5);
+            volume_cells(first, params.radii, params.hollow, |x, y, z| {
+                let (x, y, z) = (x as f32 / ex, y as f32 / ey, z as f32 / ez);
+                x * x + y * y + z * z <= 1.0
+            })
+        }
+        ShapeKind::Cylinder => {
+            let (r, height) = (params.radius, params.height);
+            let limit = round(r);
+            volume_cells(first, (r, height, r), params.hollow, |x, y, z| {
+                y >= 0 && y < height && ((x * x + z * z) as f32) <= limit
+            })
+        }
+        ShapeKind::Torus => {
+            let (major, minor) = (params.radius, params.minor_radius);
+            let limit = round(minor);
+            let extent = major + minor;
+            volume_cells(first, (extent, minor, extent), params.hollow, |x, y, z| {
+                let ring = ((x * x + z * z) as f32).sqrt() - major as f32;
+                ring * ring + (y * y) as f32 <= limit
+            })
+        }
+    }
+}
+
+// 3D Bresenham line from `a` to `b`, both ends included
+pub fn line_cells(a: (i32, i32, i32), b: (i32, i32, i32)) -> Vec<(i32, i32, i32)> {
+    let start = [a.0, a.1, a.2];
+    let end = [b.0, b.1, b.2];
+    let delta = [(end[0] - start[0]).abs(), (end[1] - start[1]).abs(), (end[2] - start[2]).abs()];
+    let step = [(end[0] - start[0]).signum(), (end[1] - start[1]).signum(), (end[2] - start[2]).signum()];
+
+    // Step one cell along the longest axis each iteration, and along the others when their error overflows
+    let major = if delta[0] >= delta[1] && delta[0] >= delta[2] {
+        0
+    } else if delta[1] >= delta[2] {
+        1
+    } else {
+        2
+    };
+    let (minor_a, minor_b) = ((major + 1) % 3, (major + 2) % 3);
+    let mut error_a = 2 * delta[minor_a] - delta[major];
+    let mut error_b = 2 * delta[minor_b] - delta[major];
+
+    let mut p = start;
+    let mut cells = vec![(p[0], p[1], p[2])];
+    for _ in 0..delta[major] {
+        p[major] += step[major];
+        if error_a >= 0 {
+            p[minor_a] += step[minor_a];
+            error_a -= 2 * delta[major];
+        }
+        if error_b >= 0 {
+            p[minor_b] += step[minor_b];
+            error_b -= 2 * delta[major];
+        }
+        error_a += 2 * delta[minor_a];
+        error_b += 2 * delta[minor_b];
+        cells.push((p[0], p[1], p[2]));
+    }
+    cells
+}
+
+// Axis-aligned rectangle with corners `a` and `b`, lying in `a`'s layer along its thinnest axis
+fn plane_cells(a: (i32, i32, i32), b: (i32, i32, i32), hollow: bool) -> Vec<(i32, i32, i32)> {
+    let a = [a.0, a.1, a.2];
+    let b = [b.0, b.1, b.2];
+    let mut min = [a[0].min(b[0]), a[1].min(b[1]), a[2].min(b[2])];
+    let mut max = [a[0].max(b[0]), a[1].max(b[1]), a[2].max(b[2])];
+
+    let flat = (0..3).min_by_key(|&axis| max[axis] - min[axis]).unwrap();
+    min[flat] = a[flat];
+    max[flat] = a[flat];
+
+    let mut cells = Vec::new();
+    for x in min[0]..=max[0] {
+        for y in min[1]..=max[1] {
+            for z in min[2]..=max[2] {
+                let p = [x, y, z];
+                let on_edge = (0..3).filter(|&axis| axis != flat).any(|axis| p[axis] == min[axis] || p[axis] == max[axis]);
+                if !hollow || on_edge {
+                    cells.push((x, y, z));
+                }
+            }
+        }
+    }
+    cells
+}
+
+// Cells around `center` (within `extent`) for which `inside` holds. Hollow shapes keep only
+// cells with a face neighbour outside the shape.
+fn volume_cells<F: Fn(i32, i32, i32) -> bool>(
+    center: (i32, i32, i32),
+    extent: (i32, i32, i32),
+    hollow: bool,
+    inside: F,
+) -> Vec<(i32, i32, i32)> {
+    let mut cells = Vec::new();
+    for x in -extent.0..=extent.0 {
+        for y in -extent.1..=extent.1 {
+            for z in -extent.2..=extent.2 {
+                if !inside(x, y, z) {
+                    continue;
+                }
+                let surface = !inside(x + 1, y, z) || !inside(x - 1, y, z)
+                    || !inside(x, y + 1, z) || !inside(x, y - 1, z)
+                    || !inside(x, y, z + 1) || !inside(x, y, z - 1);
+                if !hollow || surface {
+                    cells.push((center.0 + x, center.1 + y, center.2 + z));
+                }
+            }
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(kind: ShapeKind) -> ShapeParams {
+        ShapeParams { kind, ..ShapeParams::new() }
+    }
+
+    // Whether every cell's mirror image through `center` is also in the shape, on every axis
+    fn is_symmetric(cells: &[(i32, i32, i32)], center: (i32, i32, i32)) -> bool {
+        cells.iter().all(|&(x, y, z)| {
+            let (mx, my, mz) = (2 * center.0 - x, 2 * center.1 - y, 2 * center.2 - z);
+            cells.contains(&(mx, y, z)) && cells.contains(&(x, my, z)) && cells.contains(&(x, y, mz))
+        })
+    }
+
+    #[test]
+    fn lines_join_their_endpoints_without_gaps() {
+        for &(a, b) in &[((0, 0, 0), (7, 3, -2)), ((5, -1, 2), (1, 6, 2)), ((0, 0, 0), (-3, -3, 9)), ((2, 2, 2), (2, 2, 2))] {
+            let cells = line_cells(a, b);
+            assert_eq!(cells.first(), Some(&a));
+            assert_eq!(cells.last(), Some(&b));
+            let longest = (b.0 - a.0).abs().max((b.1 - a.1).abs()).max((b.2 - a.2).abs());
+            assert_eq!(cells.len() as i32, longest + 1);
+            // Consecutive cells touch, at least at a corner
+            for pair in cells.windows(2) {
+                let (p, q) = (pair[0], pair[1]);
+                assert!((p.0 - q.0).abs() <= 1 && (p.1 - q.1).abs() <= 1 && (p.2 - q.2).abs() <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn lines_need_a_second_anchor() {
+        assert!(shape_cells(&params(ShapeKind::Line), (0, 0, 0), None).is_empty());
+        assert_eq!(shape_cells(&params(ShapeKind::Line), (0, 0, 0), Some((2, 0, 0))).len(), 3);
+    }
+
+    #[test]
+    fn sphere_and_ellipsoid_cell_counts() {
+        let center = (10, 5, -3);
+        let mut sphere = ShapeParams { radius: 2, ..params(ShapeKind::Sphere) };
+        let cells = shape_cells(&sphere, center, None);
+        assert_eq!(cells.len(), 81);
+        assert!(is_symmetric(&cells, center));
+        sphere.hollow = true;
+        assert_eq!(shape_cells(&sphere, center, None).len(), 54);
+
+        let ellipsoid = ShapeParams { radii: (3, 1, 2), ..params(ShapeKind::Ellipsoid) };
+        let cells = shape_cells(&ellipsoid, center, None);
+        assert_eq!(cells.len(), 61);
+        assert!(is_symmetric(&cells, center));
+        assert!(cells.contains(&(13, 5, -3)) && cells.contains(&(10, 6, -3)) && cells.contains(&(10, 5, -1)));
+    }
+
+    #[test]
+    fn cylinder_stands_on_its_anchor() {
+        let cylinder = ShapeParams { radius: 2, height: 3, ..params(ShapeKind::Cylinder) };
+        let cells = shape_cells(&cylinder, (0, 4, 0), None);
+        // Radius 2 circles are 5x5 squares without their corners
+        assert_eq!(cells.len(), 21 * 3);
+        assert!(cells.iter().all(|&(_, y, _)| y >= 4 && y < 7));
+    }
+
+    #[test]
+    fn torus_has_a_hole() {
+        let torus = ShapeParams { radius: 3, minor_radius: 1, ..params(ShapeKind::Torus) };
+        let cells = shape_cells(&torus, (0, 0, 0), None);
+        assert_eq!(cells.len(), 140);
+        assert!(is_symmetric(&cells, (0, 0, 0)));
+        assert!(!cells.contains(&(0, 0, 0)));
+        assert!(cells.contains(&(3, 0, 0)) && cells.contains(&(0, 1, -3)));
+    }
+}
@@ -0,0 +1,301 @@
+use crate::{Chunk, VoxelType};
+
+// Small deterministic RNG (SplitMix64) so generation is reproducible from a seed
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    // Derive an independent stream for a chunk (and a salt such as an ore index)
+    pub fn for_chunk(seed: u64, position: (i32, i32, i32), salt: u64) -> Self {
+        let mut h = seed ^ salt.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        h ^= (position.0 as u32 as u64).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        h = h.rotate_left(21);
+        h ^= (position.1 as u32 as u64).wrapping_mul(0x94D0_49BB_1331_11EB);
+        h = h.rotate_left(21);
+        h ^= (position.2 as u32 as u64).wrapping_mul(0xD6E8_FEB8_6659_FD93);
+        Self::new(h)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Uniform float in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    // Uniform integer in [min, max] (inclusive)
+    pub fn range_i32(&mut self, min: i32, max: i32) -> i32 {
+        if max <= min {
+            return min;
+        }
+        let span = (max - min) as u64 + 1;
+        min + (self.next_u64() % span) as i32
+    }
+}
+
+// Ore vein configuration
+#[derive(Clone, Copy, PartialEq)]
+pub struct OreSettings {
+    pub voxel_type: VoxelType,
+    pub min_y: i32,         // Lowest world Y a vein may start at
+    pub max_y: i32,         // Highest world Y a vein may start at
+    pub vein_size: u32,     // Number of blocks in a vein
+    pub veins_per_chunk: f32, // Average number of veins per chunk (fractional part is a chance)
+}
+
+#[derive(Clone, PartialEq)]
+pub struct TerrainSettings {
+    pub seed: u64,
+    pub base_height: f32,       // Average surface height in blocks
+    pub height_amplitude: f32,  // How far hills rise and valleys sink from the base height
+    pub height_frequency: f32,  // How quickly the surface undulates (higher is bumpier)
+    pub ores: Vec<OreSettings>,
+    pub sea_level: i32,    // Everything at or below this world Y that isn't terrain is water
+    pub lake_radius: f32,  // Distance to the rim checked when filling depressions (0 disables lakes)
+}
+
+impl TerrainSettings {
+    pub fn new() -> Self {
+        Self {
+            seed: 1337,
+            base_height: 4.0,
+            height_amplitude: 1.0,  // Reduced height variation
+            height_frequency: 0.1,
+            ores: vec![
+                OreSettings { voxel_type: VoxelType::CoalOre, min_y: 0, max_y: 4, vein_size: 8, veins_per_chunk: 3.0 },
+                OreSettings { voxel_type: VoxelType::IronOre, min_y: 0, max_y: 3, vein_size: 5, veins_per_chunk: 2.0 },
+                OreSettings { voxel_type: VoxelType::GoldOre, min_y: 0, max_y: 2, vein_size: 4, veins_per_chunk: 0.8 },
+                OreSettings { voxel_type: VoxelType::DiamondOre, min_y: 0, max_y: 1, vein_size: 3, veins_per_chunk: 0.3 },
+            ],
+            sea_level: 3,
+            lake_radius: 6.0,
+        }
+    }
+}
+
+// Built-in generator presets, selectable in the terrain preview
+pub fn builtin_presets() -> Vec<(&'static str, TerrainSettings)> {
+    let default = TerrainSettings::new();
+    vec![
+        ("Default", default.clone()),
+        ("Flat", TerrainSettings { height_amplitude: 0.0, lake_radius: 0.0, ..default.clone() }),
+        ("Hills", TerrainSettings { base_height: 6.0, height_amplitude: 3.0, height_frequency: 0.15, ..default.clone() }),
+        ("Islands", TerrainSettings { base_height: 3.0, height_amplitude: 2.0, sea_level: 4, ..default }),
+    ]
+}
+
+// Surface height of the terrain at a world column
+pub fn surface_height(settings: &TerrainSettings, world_x: f32, world_z: f32) -> f32 {
+    settings.base_height
+        + (world_x * settings.height_frequency).sin() * settings.height_amplitude
+        + (world_z * settings.height_frequency).cos() * settings.height_amplitude
+}
+
+// Highest world Y filled with water in a column, either from the sea or a lake in a depression
+pub fn water_level(settings: &TerrainSettings, world_x: f32, world_z: f32) -> i32 {
+    let height = surface_height(settings, world_x, world_z);
+    let mut level = settings.sea_level;
+
+    if settings.lake_radius > 0.0 {
+        // Sample the rim around the column; if every side is higher, the column is a basin
+        let mut rim = f32::MAX;
+        for i in 0..8 {
+            let angle = i as f32 * std::f32::consts::FRAC_PI_4;
+            let rim_x = world_x + angle.cos() * settings.lake_radius;
+            let rim_z = world_z + angle.sin() * settings.lake_radius;
+            rim = rim.min(surface_height(settings, rim_x, rim_z));
+        }
+
+        // Fill up to one block below the lowest point of the rim
+        let lake_level = rim.floor() as i32 - 1;
+        if lake_level as f32 > height {
+            level = level.max(lake_level);
+        }
+    }
+
+    level
+}
+
+pub fn generate_chunk(settings: &TerrainSettings, position: (i32, i32, i32)) -> Chunk {
+    let mut chunk = Chunk::new(position);
+    let (x, y, z) = position;
+
+    for cx in 0..16 {
+        for cz in 0..16 {
+            // Calculate absolute world position
+            let world_x = cx as f32 + (x * 16) as f32;
+            let world_z = cz as f32 + (z * 16) as f32;
+
+            // Generate height using world coordinates
+            let height = surface_height(settings, world_x, world_z);
+            let water_level = water_level(settings, world_x, world_z);
+            let underwater = height < water_level as f32;
+
+            for cy in 0..16 {
+                let world_y = cy as i32 + y * 16;
+                let cy_f32 = world_y as f32;
+                if cy_f32 <= height {
+                    if cy_f32 > height - 1.0 && !underwater {
+                        chunk.set_voxel(cx, cy, cz, VoxelType::Grass);
+                    } else if cy_f32 > height - 3.0 {  // Reduced dirt layer (also the bed of lakes and seas)
+                        chunk.set_voxel(cx, cy, cz, VoxelType::Dirt);
+                    } else {
+                        chunk.set_voxel(cx, cy, cz, VoxelType::Stone);
+                    }
+                } else if world_y <= water_level {
+                    chunk.set_voxel(cx, cy, cz, VoxelType::Water);
+                }
+            }
+        }
+    }
+
+    for (index, ore) in settings.ores.iter().enumerate() {
+        place_ore_veins(&mut chunk, settings.seed, index as u64, ore);
+    }
+
+    chunk
+}
+
+// Scatter veins of an ore through the stone of a chunk
+fn place_ore_veins(chunk: &mut Chunk, seed: u64, salt: u64, ore: &OreSettings) {
+    let chunk_base_y = chunk.position.1 * 16;
+
+    // Clamp the depth range to this chunk
+    let min_y = ore.min_y.max(chunk_base_y) - chunk_base_y;
+    let max_y = ore.max_y.min(chunk_base_y + 15) - chunk_base_y;
+    if min_y > max_y || ore.vein_size == 0 {
+        return;
+    }
+
+    let mut rng = Rng::for_chunk(seed, chunk.position, salt + 1);
+    let mut vein_count = ore.veins_per_chunk.max(0.0).floor() as u32;
+    if rng.next_f32() < ore.veins_per_chunk.max(0.0).fract() {
+        vein_count += 1;
+    }
+
+    for _ in 0..vein_count {
+        let mut px = rng.range_i32(0, 15);
+        let mut py = rng.range_i32(min_y, max_y);
+        let mut pz = rng.range_i32(0, 15);
+
+        // Random walk from the start position, replacing stone only
+        for _ in 0..ore.vein_size {
+            if chunk.get_voxel(px as usize, py as usize, pz as usize).voxel_type == VoxelType::Stone {
+                chunk.set_voxel(px as usize, py as usize, pz as usize, ore.voxel_type);
+            }
+            match rng.range_i32(0, 5) {
+                0 => px = (px + 1).min(15),
+                1 => px = (px - 1).max(0),
+                2 => py = (py + 1).min(max_y),
+                3 => py = (py - 1).max(min_y),
+                4 => pz = (pz + 1).min(15),
+                _ => pz = (pz - 1).max(0),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write;
+
+    // Golden hashes of generated chunks; regenerate with `UPDATE_GOLDEN=1 cargo test`
+    const GOLDEN_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/terrain_hashes.txt");
+    const SEEDS: [u64; 3] = [1337, 42, 987_654_321];
+    // Every case crosses sea level and the ore depths, so its contents depend on the seed and preset
+    const CHUNKS: [(i32, i32, i32); 5] = [(0, 0, 0), (-1, 0, 1), (1, 0, -1), (5, 0, -3), (-9, 0, 14)];
+
+    // FNV-1a over the chunk position and voxel types. The hash itself doesn't depend on the
+    // platform, but the heights come from f32 `sin`/`cos`, whose last bits can differ between
+    // platforms and libm versions; a block on a height boundary can then flip and the golden
+    // file needs regenerating there.
+    fn hash_chunk(chunk: &Chunk) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let position = [chunk.position.0, chunk.position.1, chunk.position.2];
+        let bytes = position.iter().flat_map(|p| p.to_le_bytes());
+        for byte in bytes.chain(chunk.voxels.iter().map(|v| v.voxel_type as u8)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        hash
+    }
+
+    fn current_hashes() -> String {
+        let mut out = String::new();
+        for (name, preset) in builtin_presets() {
+            for &seed in &SEEDS {
+                let settings = TerrainSettings { seed, ..preset.clone() };
+                for &position in &CHUNKS {
+                    let hash = hash_chunk(&generate_chunk(&settings, position));
+                    writeln!(out, "{} {} {} {} {} {:016x}", name, seed, position.0, position.1, position.2, hash).unwrap();
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn generated_chunks_match_golden_hashes() {
+        let current = current_hashes();
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::create_dir_all(std::path::Path::new(GOLDEN_PATH).parent().unwrap()).unwrap();
+            std::fs::write(GOLDEN_PATH, &current).unwrap();
+            return;
+        }
+
+        let golden = std::fs::read_to_string(GOLDEN_PATH)
+            .expect("Missing golden hashes, run with UPDATE_GOLDEN=1 to create them");
+        let mismatches: Vec<String> = golden
+            .lines()
+            .zip(current.lines())
+            .filter(|(expected, actual)| expected != actual)
+            .map(|(expected, actual)| format!("expected {}\n     got {}", expected, actual))
+            .collect();
+
+        assert_eq!(golden.lines().count(), current.lines().count(), "Golden case list changed");
+        assert!(
+            mismatches.is_empty(),
+            "World generation changed; if intended, run with UPDATE_GOLDEN=1\n{}",
+            mismatches.join("\n")
+        );
+    }
+
+    #[test]
+    fn golden_cases_depend_on_the_seed() {
+        for &position in &CHUNKS {
+            let hashes: Vec<u64> = SEEDS
+                .iter()
+                .map(|&seed| hash_chunk(&generate_chunk(&TerrainSettings { seed, ..TerrainSettings::new() }, position)))
+                .collect();
+            assert!(hashes[0] != hashes[1] && hashes[1] != hashes[2], "Chunk {:?} ignores the seed", position);
+        }
+    }
+
+    #[test]
+    fn generation_is_deterministic() {
+        let settings = TerrainSettings::new();
+        let a = generate_chunk(&settings, (2, 0, -1));
+        let b = generate_chunk(&settings, (2, 0, -1));
+        assert_eq!(hash_chunk(&a), hash_chunk(&b));
+    }
+
+    #[test]
+    fn seed_changes_ore_placement() {
+        let a = generate_chunk(&TerrainSettings { seed: 1, ..TerrainSettings::new() }, (0, 0, 0));
+        let b = generate_chunk(&TerrainSettings { seed: 2, ..TerrainSettings::new() }, (0, 0, 0));
+        assert_ne!(hash_chunk(&a), hash_chunk(&b));
+    }
+}
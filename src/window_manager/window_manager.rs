@@ -1,6 +1,18 @@
 pub mod windows{
-    use egui::{Modifiers, Slider, Ui};
-    use crate::VoxelType;
+    use std::collections::VecDeque;
+    use std::time::Instant;
+    use egui::{Color32, Modifiers, Slider, Stroke, TextureId, Ui};
+    use egui_sdl2_gl::painter::Painter;
+    use crate::camera_path::{CameraPath, Interpolation, Keyframe};
+    use crate::camera_rig::CameraView;
+    use crate::brush::BrushShape;
+    use crate::flood_fill::FillSettings;
+    use crate::gamepad::GamepadSettings;
+    use crate::input_map::{Action, Binding, InputMap, CONTROLS_FILE};
+    use crate::placement::PlacementRules;
+    use crate::shapes::{self, ShapeKind, ShapeParams};
+    use crate::terrain::{self, TerrainSettings};
+    use crate::{GameMode, MovementMode, VoxelType};
 
     // Define ShaderType enum at the top level
     #[derive(Clone, Copy, PartialEq)]
@@ -12,71 +24,310 @@ pub mod windows{
         Default,
     }
 
+    // Number of hotbar slots, selected with keys 1-9 by default
+    pub const HOTBAR_SLOTS: usize = 9;
+
     #[derive(Clone)]
     pub struct SandboxWindow {
         pub selected_block: VoxelType,
+        pub hotbar: [VoxelType; HOTBAR_SLOTS],
+        pub active_slot: usize,
         pub movement_speed: f32,
         pub mouse_sensitivity: f32,
+        pub movement_mode: MovementMode,
+        pub acceleration: f32,
+        pub damping: f32,
+        pub fov: f32,
+        pub camera_view: CameraView,
+        pub boom_length: f32,
+        pub turntable_speed: f32,
+        pub gamepad_settings: GamepadSettings,
         pub selected_shader: ShaderType,
-
+        pub target_water: bool,
+        pub game_mode: GameMode,
+        pub placement_rules: PlacementRules,
+        pub brush_shape: BrushShape,
+        pub brush_radius: i32,
+        pub drag_paint: bool,
+        pub fill_mode: bool,
+        pub fill_settings: FillSettings,
+        pub undo_requested: bool,
+        pub redo_requested: bool,
     }
     
     impl SandboxWindow {
         pub fn new() -> Self {
             Self {
                 selected_block: VoxelType::Dirt,
-                movement_speed: 1.0,
+                hotbar: [
+                    VoxelType::Dirt,
+                    VoxelType::Grass,
+                    VoxelType::Stone,
+                    VoxelType::Wood,
+                    VoxelType::Leaves,
+                    VoxelType::Light,
+                    VoxelType::CoalOre,
+                    VoxelType::IronOre,
+                    VoxelType::Water,
+                ],
+                active_slot: 0,
+                movement_speed: 6.0,
                 mouse_sensitivity: 0.1,
+                movement_mode: MovementMode::Fly,
+                acceleration: 12.0,
+                damping: 8.0,
+                fov: 70.0,
+                camera_view: CameraView::FirstPerson,
+                boom_length: 4.0,
+                turntable_speed: 0.0,
+                gamepad_settings: GamepadSettings::new(),
                 selected_shader: ShaderType::Balanced,
+                target_water: false,
+                game_mode: GameMode::Creative,
+                placement_rules: PlacementRules::new(),
+                brush_shape: BrushShape::Single,
+                brush_radius: 2,
+                drag_paint: false,
+                fill_mode: false,
+                fill_settings: FillSettings::new(),
+                undo_requested: false,
+                redo_requested: false,
             }
         }
     
-        pub fn ui(&mut self, ctx: &egui::Context, ui: &mut Ui) {
+        pub fn ui(&mut self, ctx: &egui::Context, ui: &mut Ui, bindings: &InputMap) {
             let _ = ctx;
-            self.scene_settings(ui);
+            self.scene_settings(ui, bindings);
         
         }
-        pub fn scene_settings(&mut self, ui: &mut Ui) {
+
+        // Make a hotbar slot active and select its block
+        pub fn select_slot(&mut self, slot: usize) {
+            self.active_slot = slot % HOTBAR_SLOTS;
+            self.selected_block = self.hotbar[self.active_slot];
+        }
+
+        // Move the active slot by `offset`, wrapping around the ends (used by the mouse wheel)
+        pub fn cycle_slot(&mut self, offset: i32) {
+            let slot = (self.active_slot as i32 + offset).rem_euclid(HOTBAR_SLOTS as i32);
+            self.select_slot(slot as usize);
+        }
+
+        // Put a palette block into the active hotbar slot and select it
+        pub fn assign_to_active_slot(&mut self, voxel_type: VoxelType) {
+            self.hotbar[self.active_slot] = voxel_type;
+            self.selected_block = voxel_type;
+        }
+
+        pub fn hotbar_ui(&self, ctx: &egui::Context) {
+            egui::Area::new(egui::Id::new("hotbar"))
+                .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -10.0))
+                .order(egui::Order::Foreground)
+                .interactable(false)
+                .show(ctx, |ui| {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            for (slot, &voxel_type) in self.hotbar.iter().enumerate() {
+                                let active = slot == self.active_slot;
+                                let stroke = if active {
+                                    Stroke::new(2.0, Color32::WHITE)
+                                } else {
+                                    Stroke::new(1.0, Color32::from_gray(60))
+                                };
+                                egui::Frame::none()
+                                    .stroke(stroke)
+                                    .inner_margin(4.0)
+                                    .show(ui, |ui| {
+                                        ui.set_width(52.0);
+                                        ui.vertical_centered(|ui| {
+                                            let (rect, _) = ui.allocate_exact_size(egui::vec2(24.0, 24.0), egui::Sense::hover());
+                                            ui.painter().rect_filled(rect, 3.0, voxel_color(voxel_type));
+                                            ui.small(format!("{} {:?}", slot + 1, voxel_type));
+                                        });
+                                    });
+                            }
+                        });
+                    });
+                });
+        }
+        pub fn scene_settings(&mut self, ui: &mut Ui, bindings: &InputMap) {
             ui.heading("Block Selection");
             ui.separator();
+
+            ui.label("Hotbar (click a slot, then a block below to fill it):");
+            ui.horizontal_wrapped(|ui| {
+                for slot in 0..HOTBAR_SLOTS {
+                    let label = format!("{}: {:?}", slot + 1, self.hotbar[slot]);
+                    if ui.selectable_label(slot == self.active_slot, label).clicked() {
+                        self.select_slot(slot);
+                    }
+                }
+            });
+            ui.separator();
             
             ui.horizontal(|ui| {
                 if ui.selectable_label(self.selected_block == VoxelType::Dirt, "Dirt").clicked() {
-                    self.selected_block = VoxelType::Dirt;
+                    self.assign_to_active_slot(VoxelType::Dirt);
                 }
                 if ui.selectable_label(self.selected_block == VoxelType::Grass, "Grass").clicked() {
-                    self.selected_block = VoxelType::Grass;
+                    self.assign_to_active_slot(VoxelType::Grass);
                 }
                 if ui.selectable_label(self.selected_block == VoxelType::Stone, "Stone").clicked() {
-                    self.selected_block = VoxelType::Stone;
+                    self.assign_to_active_slot(VoxelType::Stone);
                 }
             });
             
             ui.horizontal(|ui| {
                 if ui.selectable_label(self.selected_block == VoxelType::Wood, "Wood").clicked() {
-                    self.selected_block = VoxelType::Wood;
+                    self.assign_to_active_slot(VoxelType::Wood);
                 }
                 if ui.selectable_label(self.selected_block == VoxelType::Leaves, "Leaves").clicked() {
-                    self.selected_block = VoxelType::Leaves;
+                    self.assign_to_active_slot(VoxelType::Leaves);
                 }
                 if ui.selectable_label(self.selected_block == VoxelType::Light, "Light").clicked() {
-                    self.selected_block = VoxelType::Light;
+                    self.assign_to_active_slot(VoxelType::Light);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                if ui.selectable_label(self.selected_block == VoxelType::CoalOre, "Coal Ore").clicked() {
+                    self.assign_to_active_slot(VoxelType::CoalOre);
+                }
+                if ui.selectable_label(self.selected_block == VoxelType::IronOre, "Iron Ore").clicked() {
+                    self.assign_to_active_slot(VoxelType::IronOre);
+                }
+                if ui.selectable_label(self.selected_block == VoxelType::GoldOre, "Gold Ore").clicked() {
+                    self.assign_to_active_slot(VoxelType::GoldOre);
+                }
+                if ui.selectable_label(self.selected_block == VoxelType::DiamondOre, "Diamond Ore").clicked() {
+                    self.assign_to_active_slot(VoxelType::DiamondOre);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                if ui.selectable_label(self.selected_block == VoxelType::Water, "Water").clicked() {
+                    self.assign_to_active_slot(VoxelType::Water);
+                }
+            });
+            ui.checkbox(&mut self.target_water, "Target Water")
+                .on_hover_text("Break and place against water instead of passing through it");
+            
+            ui.separator();
+            ui.heading("Game Mode");
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut self.game_mode, GameMode::Creative, "Creative")
+                    .on_hover_text("Instant breaking, infinite blocks, flight and the editing tools");
+                ui.radio_value(&mut self.game_mode, GameMode::Survival, "Survival")
+                    .on_hover_text("Walking only. Hold break to mine blocks into your inventory; harder blocks take longer");
+                ui.radio_value(&mut self.game_mode, GameMode::Spectator, "Spectator")
+                    .on_hover_text("Fly through blocks without changing anything");
+            });
+            ui.label("Also /gamemode <creative|survival|spectator> on the command line.");
+            
+            egui::CollapsingHeader::new("Placement Rules").show(ui, |ui| {
+                let rules = &mut self.placement_rules;
+                ui.checkbox(&mut rules.require_support, "Require Support")
+                    .on_hover_text("Blocks must touch a solid block (grass must sit on one)");
+                // Limits can only tighten the loaded 3x3 chunk area; blocks outside it have nowhere to go
+                ui.add(Slider::new(&mut rules.max_y, 0..=15).text("Height Limit"));
+                ui.add(Slider::new(&mut rules.min_y, 0..=15).text("Bottom Limit"));
+                ui.add(Slider::new(&mut rules.border_min, -16..=0).text("Border Min X/Z"));
+                ui.add(Slider::new(&mut rules.border_max, 0..=31).text("Border Max X/Z"));
+            });
+            
+            ui.separator();
+            ui.heading("Brush");
+            if self.game_mode != GameMode::Creative {
+                ui.weak("Brushes, bucket fill, shapes and undo only work in Creative.");
+            }
+            ui.horizontal(|ui| {
+                for shape in BrushShape::ALL {
+                    ui.radio_value(&mut self.brush_shape, shape, format!("{:?}", shape));
+                }
+            });
+            ui.add_enabled(
+                self.brush_shape != BrushShape::Single,
+                Slider::new(&mut self.brush_radius, 1..=8).text("Radius"),
+            );
+            ui.checkbox(&mut self.drag_paint, "Drag Painting")
+                .on_hover_text("Keep applying the brush while a mouse button is held");
+            ui.checkbox(&mut self.fill_mode, "Bucket Fill")
+                .on_hover_text("Left click replaces the connected blocks of the clicked type, right click fills the connected empty space");
+            if self.fill_mode {
+                let fill = &mut self.fill_settings;
+                ui.add(Slider::new(&mut fill.max_volume, 1..=32768).logarithmic(true).text("Max Volume"));
+                ui.checkbox(&mut fill.use_bounds, "Limit to Selection");
+                if fill.use_bounds {
+                    for (label, corner) in [("Min", &mut fill.bounds_min), ("Max", &mut fill.bounds_max)] {
+                        ui.horizontal(|ui| {
+                            ui.label(label);
+                            ui.add(egui::DragValue::new(&mut corner.0).prefix("x "));
+                            ui.add(egui::DragValue::new(&mut corner.1).prefix("y "));
+                            ui.add(egui::DragValue::new(&mut corner.2).prefix("z "));
+                        });
+                    }
+                }
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Undo").clicked() {
+                    self.undo_requested = true;
+                }
+                if ui.button("Redo").clicked() {
+                    self.redo_requested = true;
                 }
             });
             
             ui.separator();
             ui.heading("Movement Settings");
             
-            ui.add(Slider::new(&mut self.movement_speed, 0.1..=2.0).text("Movement Speed"));
+            ui.add(Slider::new(&mut self.movement_speed, 0.5..=20.0).text("Movement Speed (blocks/s)"));
             ui.add(Slider::new(&mut self.mouse_sensitivity, 0.01..=0.3).text("Mouse Sensitivity"));
+            // Survival always plays on foot and spectator always flies
+            ui.add_enabled_ui(self.game_mode == GameMode::Creative, |ui| {
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.movement_mode, MovementMode::Fly, "Free Fly")
+                        .on_hover_text("Forward moves along the view direction");
+                    ui.radio_value(&mut self.movement_mode, MovementMode::Walk, "Level Walk")
+                        .on_hover_text("Forward and strafing stay level; no gravity");
+                    ui.radio_value(&mut self.movement_mode, MovementMode::Player, "Player")
+                        .on_hover_text("Gravity and collision; jump to climb");
+                });
+            });
+            ui.add(Slider::new(&mut self.acceleration, 1.0..=40.0).text("Acceleration"));
+            ui.add(Slider::new(&mut self.damping, 1.0..=40.0).text("Damping"));
+            ui.add(Slider::new(&mut self.fov, 30.0..=110.0).text("Field of View (degrees)"));
             
             ui.separator();
-            ui.label("Controls:");
-            ui.label("WASD - Move");
-            ui.label("Space/Shift - Up/Down");
-            ui.label("Left Click - Break Block");
-            ui.label("Right Click - Place Block");
-            ui.label("ESC - Toggle Mouse Capture");
+            ui.heading("Camera View");
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut self.camera_view, CameraView::FirstPerson, "First Person");
+                ui.radio_value(&mut self.camera_view, CameraView::ThirdPerson, "Third Person")
+                    .on_hover_text("Follows behind the head, moving in when blocks get in the way");
+                ui.radio_value(&mut self.camera_view, CameraView::Orbit, "Orbit")
+                    .on_hover_text("Circles the block you were looking at; scroll to zoom");
+            });
+            ui.add(Slider::new(&mut self.boom_length, 1.0..=12.0).text("Third Person Distance"));
+            ui.add(Slider::new(&mut self.turntable_speed, -90.0..=90.0).text("Turntable Speed (degrees/s)"));
+            
+            ui.separator();
+            ui.collapsing("Gamepad", |ui| {
+                let pad = &mut self.gamepad_settings;
+                ui.add(Slider::new(&mut pad.look_speed, 30.0..=540.0).text("Look Speed (degrees/s)"));
+                ui.add(Slider::new(&mut pad.deadzone, 0.0..=0.5).text("Stick Deadzone"));
+                ui.add(Slider::new(&mut pad.look_curve, 1.0..=3.0).text("Look Curve"))
+                    .on_hover_text("Higher values give finer aim with small stick movements");
+                ui.checkbox(&mut pad.invert_look_y, "Invert Look Y");
+                ui.label("Left stick moves, right stick looks. A/B rise and sink (A jumps in player mode).");
+                ui.label("Click the left stick to sprint and the right stick to crouch.");
+                ui.label("RT breaks, LT places, X picks, LB/RB cycle the hotbar, Y toggles Walk/Fly.");
+                ui.label("Start opens the menus: D-pad moves between controls, A activates, B backs out.");
+            });
+            
+            ui.separator();
+            ui.label("Controls (change them in the Controls window):");
+            for line in bindings.help_lines() {
+                ui.label(line);
+            }
             ui.add_space(10.0);
             ui.separator();
             ui.add_space(10.0);
@@ -97,6 +348,650 @@ pub mod windows{
         
     }
     
+    // Width and height of the terrain preview in blocks (one pixel per column)
+    const PREVIEW_SIZE: usize = 128;
+
+    pub struct TerrainPreviewWindow {
+        pub settings: TerrainSettings,
+        pub texture: Option<TextureId>,
+        pub dirty: bool,
+        pub apply_requested: bool,
+    }
+
+    impl TerrainPreviewWindow {
+        pub fn new(settings: TerrainSettings) -> Self {
+            Self {
+                settings,
+                texture: None,
+                dirty: true,
+                apply_requested: false,
+            }
+        }
+
+        pub fn ui(&mut self, ui: &mut Ui) {
+            let before = self.settings.clone();
+
+            ui.heading("Terrain Shape");
+            egui::ComboBox::from_label("Preset")
+                .selected_text("Choose...")
+                .show_ui(ui, |ui| {
+                    for (name, preset) in terrain::builtin_presets() {
+                        if ui.selectable_label(false, name).clicked() {
+                            self.settings = TerrainSettings { seed: self.settings.seed, ..preset };
+                        }
+                    }
+                });
+            ui.horizontal(|ui| {
+                ui.label("Seed");
+                ui.add(egui::DragValue::new(&mut self.settings.seed));
+            });
+            ui.add(Slider::new(&mut self.settings.base_height, 1.0..=12.0).text("Base Height"));
+            ui.add(Slider::new(&mut self.settings.height_amplitude, 0.0..=6.0).text("Amplitude"));
+            ui.add(Slider::new(&mut self.settings.height_frequency, 0.01..=0.5).text("Frequency"));
+
+            ui.separator();
+            ui.heading("Water");
+            ui.add(Slider::new(&mut self.settings.sea_level, 0..=12).text("Sea Level"));
+            ui.add(Slider::new(&mut self.settings.lake_radius, 0.0..=16.0).text("Lake Radius"));
+
+            egui::CollapsingHeader::new("Ores").show(ui, |ui| {
+                for ore in &mut self.settings.ores {
+                    ui.label(format!("{:?}", ore.voxel_type));
+                    ui.add(Slider::new(&mut ore.veins_per_chunk, 0.0..=8.0).text("Veins per Chunk"));
+                    ui.add(Slider::new(&mut ore.vein_size, 1..=16).text("Vein Size"));
+                    ui.add(Slider::new(&mut ore.max_y, 0..=15).text("Max Y"));
+                }
+            });
+
+            if self.settings != before {
+                self.dirty = true;
+            }
+
+            ui.separator();
+            if let Some(texture) = self.texture {
+                ui.image((texture, egui::vec2(256.0, 256.0)));
+                ui.label(egui::RichText::new("Top-down view; the outline marks the loaded chunks.").small());
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("Apply").on_hover_text("Regenerate the loaded world with these settings").clicked() {
+                    self.apply_requested = true;
+                }
+                if ui.button("Reset").clicked() {
+                    self.settings = TerrainSettings::new();
+                    self.dirty = true;
+                }
+            });
+        }
+
+        // Re-render the preview into its user texture when the settings changed
+        pub fn update_texture(&mut self, painter: &mut Painter) {
+            if !self.dirty {
+                return;
+            }
+            self.dirty = false;
+
+            let pixels = self.render_pixels();
+            match self.texture {
+                Some(texture) => painter.update_user_texture_data(texture, &pixels),
+                None => {
+                    self.texture = Some(painter.new_user_texture((PREVIEW_SIZE, PREVIEW_SIZE), &pixels, false));
+                }
+            }
+        }
+
+        fn render_pixels(&self) -> Vec<Color32> {
+            // Center the preview on the middle of the loaded 3x3 chunks (blocks -16..32)
+            let origin = 8 - PREVIEW_SIZE as i32 / 2;
+            let mut pixels = Vec::with_capacity(PREVIEW_SIZE * PREVIEW_SIZE);
+
+            for pz in 0..PREVIEW_SIZE as i32 {
+                for px in 0..PREVIEW_SIZE as i32 {
+                    let world_x = (origin + px) as f32;
+                    let world_z = (origin + pz) as f32;
+                    let height = terrain::surface_height(&self.settings, world_x, world_z);
+                    let water_level = terrain::water_level(&self.settings, world_x, world_z);
+
+                    let mut color = if height < water_level as f32 {
+                        // Deeper water is darker
+                        let depth = (water_level as f32 - height).min(8.0);
+                        Color32::from_rgb(25, (110.0 - depth * 10.0) as u8, (190.0 - depth * 12.0) as u8)
+                    } else {
+                        // Higher land fades from grass to bare rock
+                        let t = ((height - water_level as f32) / 8.0).clamp(0.0, 1.0);
+                        Color32::from_rgb(
+                            (60.0 + t * 120.0) as u8,
+                            (160.0 + t * 10.0) as u8,
+                            (50.0 + t * 100.0) as u8,
+                        )
+                    };
+
+                    // Outline the loaded area
+                    let on_x_edge = (origin + px == -16 || origin + px == 31) && (-16..32).contains(&(origin + pz));
+                    let on_z_edge = (origin + pz == -16 || origin + pz == 31) && (-16..32).contains(&(origin + px));
+                    if on_x_edge || on_z_edge {
+                        color = Color32::from_rgb(240, 240, 240);
+                    }
+
+                    pixels.push(color);
+                }
+            }
+
+            pixels
+        }
+    }
+
+    // Approximate block colors, matching the compute shaders
+    pub fn voxel_color(voxel_type: VoxelType) -> Color32 {
+        match voxel_type {
+            VoxelType::Air => Color32::TRANSPARENT,
+            VoxelType::Dirt => Color32::from_rgb(153, 77, 25),
+            VoxelType::Grass => Color32::from_rgb(25, 204, 25),
+            VoxelType::Stone => Color32::from_rgb(128, 128, 128),
+            VoxelType::Wood => Color32::from_rgb(102, 51, 25),
+            VoxelType::Leaves => Color32::from_rgb(0, 128, 0),
+            VoxelType::Light => Color32::from_rgb(255, 230, 178),
+            VoxelType::CoalOre => Color32::from_rgb(51, 51, 51),
+            VoxelType::IronOre => Color32::from_rgb(191, 153, 128),
+            VoxelType::GoldOre => Color32::from_rgb(242, 204, 51),
+            VoxelType::DiamondOre => Color32::from_rgb(102, 230, 242),
+            VoxelType::Water => Color32::from_rgb(25, 89, 153),
+        }
+    }
+
+    // Anchor-driven drawing of lines, planes and solids
+    pub struct ShapeToolWindow {
+        pub enabled: bool,
+        pub params: ShapeParams,
+        pub anchors: [Option<(i32, i32, i32)>; 2],
+        pub build_requested: bool,
+    }
+
+    impl ShapeToolWindow {
+        pub fn new() -> Self {
+            Self {
+                enabled: false,
+                params: ShapeParams::new(),
+                anchors: [None, None],
+                build_requested: false,
+            }
+        }
+
+        // Cells the shape would fill with the current anchors
+        pub fn preview_cells(&self) -> Vec<(i32, i32, i32)> {
+            if !self.enabled {
+                return Vec::new();
+            }
+            match self.anchors[0] {
+                Some(first) => shapes::shape_cells(&self.params, first, self.anchors[1]),
+                None => Vec::new(),
+            }
+        }
+
+        pub fn ui(&mut self, ui: &mut Ui) {
+            ui.checkbox(&mut self.enabled, "Use Shape Tool")
+                .on_hover_text("While enabled, clicks set anchors instead of breaking and placing");
+
+            let params = &mut self.params;
+            egui::ComboBox::from_label("Shape")
+                .selected_text(format!("{:?}", params.kind))
+                .show_ui(ui, |ui| {
+                    for kind in ShapeKind::ALL {
+                        ui.selectable_value(&mut params.kind, kind, format!("{:?}", kind));
+                    }
+                });
+            if params.kind != ShapeKind::Line {
+                ui.checkbox(&mut params.hollow, "Hollow");
+            }
+            match params.kind {
+                ShapeKind::Line | ShapeKind::Plane => {}
+                ShapeKind::Sphere => {
+                    ui.add(Slider::new(&mut params.radius, 1..=16).text("Radius"));
+                }
+                ShapeKind::Ellipsoid => {
+                    ui.add(Slider::new(&mut params.radii.0, 1..=16).text("Radius X"));
+                    ui.add(Slider::new(&mut params.radii.1, 1..=16).text("Radius Y"));
+                    ui.add(Slider::new(&mut params.radii.2, 1..=16).text("Radius Z"));
+                }
+                ShapeKind::Cylinder => {
+                    ui.add(Slider::new(&mut params.radius, 1..=16).text("Radius"));
+                    ui.add(Slider::new(&mut params.height, 1..=16).text("Height"));
+                }
+                ShapeKind::Torus => {
+                    ui.add(Slider::new(&mut params.radius, 1..=16).text("Ring Radius"));
+                    ui.add(Slider::new(&mut params.minor_radius, 1..=8).text("Tube Radius"));
+                }
+            }
+
+            ui.separator();
+            let names = ["First anchor (break)", "Second anchor (place)"];
+            for (i, name) in names.iter().enumerate().take(params.kind.anchor_count()) {
+                match self.anchors[i] {
+                    Some((x, y, z)) => ui.label(format!("{}: {} / {} / {}", name, x, y, z)),
+                    None => ui.label(format!("{}: not set", name)),
+                };
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Build").clicked() {
+                    self.build_requested = true;
+                }
+                if ui.button("Clear Anchors").clicked() {
+                    self.anchors = [None, None];
+                }
+            });
+        }
+    }
+
+    pub struct CameraPathWindow {
+        pub path: CameraPath,
+        pub file_name: String,
+        pub looping: bool,
+        pub playhead: Option<f32>, // Distance along the path while playing
+        pub record_requested: bool,
+        pub play_requested: bool,
+        pub stop_requested: bool,
+        pub jump_requested: Option<usize>,
+        selected: Option<usize>,
+        status: String,
+    }
+
+    impl CameraPathWindow {
+        pub fn new() -> Self {
+            Self {
+                path: CameraPath::new(),
+                file_name: "camera_path.txt".to_string(),
+                looping: false,
+                playhead: None,
+                record_requested: false,
+                play_requested: false,
+                stop_requested: false,
+                jump_requested: None,
+                selected: None,
+                status: String::new(),
+            }
+        }
+
+        pub fn ui(&mut self, ui: &mut Ui) {
+            egui::ComboBox::from_label("Interpolation")
+                .selected_text(format!("{:?}", self.path.interpolation))
+                .show_ui(ui, |ui| {
+                    for interpolation in Interpolation::ALL {
+                        ui.selectable_value(&mut self.path.interpolation, interpolation, format!("{:?}", interpolation));
+                    }
+                });
+            ui.add(Slider::new(&mut self.path.speed, 0.5..=30.0).text("Speed (blocks/s)"));
+            ui.checkbox(&mut self.looping, "Loop");
+
+            ui.horizontal(|ui| {
+                if ui.button("Add Keyframe").on_hover_text("Record the current camera position, angles and FOV").clicked() {
+                    self.record_requested = true;
+                }
+                if self.playhead.is_some() {
+                    if ui.button("Stop").clicked() {
+                        self.stop_requested = true;
+                    }
+                } else if ui.button("Play").clicked() {
+                    self.play_requested = true;
+                }
+            });
+
+            let length = self.path.length();
+            ui.label(format!(
+                "{} keyframes, {:.1} blocks, {:.1} s",
+                self.path.keyframes.len(),
+                length,
+                length / self.path.speed
+            ));
+            self.timeline_ui(ui, length);
+
+            // Editor for the selected keyframe
+            if let Some(index) = self.selected.filter(|&i| i < self.path.keyframes.len()) {
+                ui.separator();
+                ui.label(format!("Keyframe {}", index + 1));
+                let key = &mut self.path.keyframes[index];
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut key.position.0).speed(0.1).prefix("x: "));
+                    ui.add(egui::DragValue::new(&mut key.position.1).speed(0.1).prefix("y: "));
+                    ui.add(egui::DragValue::new(&mut key.position.2).speed(0.1).prefix("z: "));
+                });
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut key.yaw).speed(0.5).prefix("yaw: "));
+                    ui.add(egui::DragValue::new(&mut key.pitch).speed(0.5).clamp_range(-89.0..=89.0).prefix("pitch: "));
+                    ui.add(egui::DragValue::new(&mut key.fov).speed(0.5).clamp_range(30.0..=110.0).prefix("fov: "));
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Go To").clicked() {
+                        self.jump_requested = Some(index);
+                    }
+                    if ui.button("Earlier").clicked() && index > 0 {
+                        self.path.keyframes.swap(index, index - 1);
+                        self.path.unwrap_yaws();
+                        self.selected = Some(index - 1);
+                    }
+                    if ui.button("Later").clicked() && index + 1 < self.path.keyframes.len() {
+                        self.path.keyframes.swap(index, index + 1);
+                        self.path.unwrap_yaws();
+                        self.selected = Some(index + 1);
+                    }
+                    if ui.button("Delete").clicked() {
+                        self.path.keyframes.remove(index);
+                        self.selected = None;
+                    }
+                });
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("File:");
+                ui.text_edit_singleline(&mut self.file_name);
+            });
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    self.status = match std::fs::write(&self.file_name, self.path.to_text()) {
+                        Ok(()) => format!("Saved {}", self.file_name),
+                        Err(error) => format!("Couldn't save: {}", error),
+                    };
+                }
+                if ui.button("Load").clicked() {
+                    self.status = match std::fs::read_to_string(&self.file_name) {
+                        Ok(text) => match CameraPath::from_text(&text) {
+                            Ok(path) => {
+                                self.path = path;
+                                self.selected = None;
+                                self.stop_requested = true;
+                                format!("Loaded {}", self.file_name)
+                            }
+                            Err(error) => error.to_string(),
+                        },
+                        Err(error) => format!("Couldn't load: {}", error),
+                    };
+                }
+            });
+            if !self.status.is_empty() {
+                ui.label(&self.status);
+            }
+        }
+
+        // Strip with a marker per keyframe, placed by distance along the path, and the
+        // playhead. Clicking a marker selects that keyframe.
+        fn timeline_ui(&mut self, ui: &mut Ui, length: f32) {
+            let (rect, response) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 36.0), egui::Sense::click());
+            let painter = ui.painter_at(rect);
+            painter.rect_filled(rect, 4.0, Color32::from_gray(30));
+            let x_at = |distance: f32| {
+                let fraction = if length > 0.0 { distance / length } else { 0.0 };
+                rect.left() + 10.0 + fraction * (rect.width() - 20.0)
+            };
+            painter.line_segment(
+                [egui::pos2(x_at(0.0), rect.center().y), egui::pos2(x_at(length), rect.center().y)],
+                Stroke::new(2.0, Color32::GRAY),
+            );
+
+            let distances = self.path.keyframe_distances();
+            for (index, &distance) in distances.iter().enumerate() {
+                let color = if self.selected == Some(index) { Color32::YELLOW } else { Color32::LIGHT_BLUE };
+                painter.circle_filled(egui::pos2(x_at(distance), rect.center().y), 5.0, color);
+            }
+            if let Some(playhead) = self.playhead {
+                let x = x_at(playhead);
+                painter.line_segment([egui::pos2(x, rect.top() + 4.0), egui::pos2(x, rect.bottom() - 4.0)], Stroke::new(2.0, Color32::RED));
+            }
+
+            if let Some(pointer) = response.interact_pointer_pos().filter(|_| response.clicked()) {
+                self.selected = distances
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &distance)| (index, (x_at(distance) - pointer.x).abs()))
+                    .filter(|&(_, gap)| gap <= 8.0)
+                    .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                    .map(|(index, _)| index);
+            }
+        }
+
+        // Add a keyframe after the selected one, or at the end
+        pub fn record(&mut self, key: Keyframe) {
+            let index = match self.selected {
+                Some(selected) if selected < self.path.keyframes.len() => selected + 1,
+                _ => self.path.keyframes.len(),
+            };
+            self.path.keyframes.insert(index, key);
+            self.path.unwrap_yaws();
+            self.selected = Some(index);
+        }
+    }
+
+    // Lists every action with its bindings. Clicking a binding removes it; "+" waits for the
+    // next key or mouse button, which main passes back through `capture`.
+    pub struct ControlsWindow {
+        pub bindings: InputMap,
+        pub capturing: Option<Action>,
+        status: String,
+    }
+
+    impl ControlsWindow {
+        pub fn new(bindings: InputMap) -> Self {
+            Self { bindings, capturing: None, status: String::new() }
+        }
+
+        pub fn ui(&mut self, ui: &mut Ui) {
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                egui::Grid::new("controls_grid").num_columns(2).striped(true).show(ui, |ui| {
+                    for action in Action::all() {
+                        ui.label(action.label());
+                        ui.horizontal(|ui| {
+                            for binding in self.bindings.bindings_for(action) {
+                                if ui.button(binding.to_string()).on_hover_text("Click to remove").clicked() {
+                                    self.bindings.unbind(action, binding);
+                                }
+                            }
+                            if self.capturing == Some(action) {
+                                ui.label("Press a key, combination or mouse button outside this window... (Esc cancels)");
+                            } else if ui.button("+").on_hover_text("Add a binding").clicked() {
+                                self.capturing = Some(action);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    self.status = match std::fs::write(CONTROLS_FILE, self.bindings.to_text()) {
+                        Ok(()) => format!("Saved {}", CONTROLS_FILE),
+                        Err(error) => format!("Couldn't save: {}", error),
+                    };
+                }
+                if ui.button("Reset to Defaults").clicked() {
+                    self.bindings = InputMap::new();
+                    self.capturing = None;
+                    self.status = "Reset to the default controls".to_string();
+                }
+            });
+            if !self.status.is_empty() {
+                ui.label(&self.status);
+            }
+        }
+
+        // Bind the captured input to the action waiting for it
+        pub fn capture(&mut self, binding: Binding) {
+            if let Some(action) = self.capturing.take() {
+                self.bindings.bind(action, binding);
+                self.status = format!("{} bound to {}", action.label(), binding);
+            }
+        }
+    }
+
+    // Prompt at the bottom left for typed commands; main runs whatever is submitted
+    pub struct CommandLine {
+        pub open: bool,
+        pub submitted: Option<String>,
+        text: String,
+        focus_requested: bool,
+    }
+
+    impl CommandLine {
+        pub fn new() -> Self {
+            Self { open: false, submitted: None, text: String::new(), focus_requested: false }
+        }
+
+        pub fn open(&mut self) {
+            self.open = true;
+            self.focus_requested = true;
+            self.text.clear();
+        }
+
+        pub fn ui(&mut self, ctx: &egui::Context) {
+            if !self.open {
+                return;
+            }
+            egui::Area::new(egui::Id::new("command_line"))
+                .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(10.0, -10.0))
+                .order(egui::Order::Foreground)
+                .show(ctx, |ui| {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut self.text)
+                                .desired_width(400.0)
+                                .hint_text("/gamemode survival"),
+                        );
+                        if self.focus_requested {
+                            self.focus_requested = false;
+                            response.request_focus();
+                        }
+                        // Enter runs the command; Escape or clicking away just closes the prompt
+                        if response.lost_focus() {
+                            if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                                self.submitted = Some(std::mem::take(&mut self.text));
+                            }
+                            self.open = false;
+                        }
+                        ui.weak("Enter runs the command, Esc cancels");
+                    });
+                });
+        }
+    }
+
+    // Per-frame values shown by the HUD
+    pub struct HudInfo {
+        pub camera_position: (f32, f32, f32),
+        pub camera_chunk: (i32, i32, i32),
+        pub target: Option<((i32, i32, i32), VoxelType)>,
+        pub selected_block: VoxelType,
+        pub elapsed_time: f32, // Seconds since startup, as passed to the shaders
+        pub sim_ticks: u32,    // Fixed simulation ticks run this frame
+        pub gamepads: usize,   // Connected controllers
+        pub hud_keys: String,  // Bindings of the HUD toggle, for the hint at the bottom
+        pub game_mode: GameMode,
+        pub block_count: Option<u32>, // Selected blocks left in the inventory, in survival
+    }
+
+    // Number of frames the frame-time readout is averaged over
+    const FRAME_HISTORY: usize = 60;
+    // How long feedback messages stay on screen, in seconds
+    const MESSAGE_DURATION: f32 = 2.0;
+
+    pub struct Hud {
+        pub visible: bool,
+        frame_times: VecDeque<f32>,
+        message: Option<(String, Instant)>,
+    }
+
+    impl Hud {
+        pub fn new() -> Self {
+            Self {
+                visible: true,
+                frame_times: VecDeque::with_capacity(FRAME_HISTORY),
+                message: None,
+            }
+        }
+
+        // Show a short notice above the hotbar, e.g. why a block couldn't be placed
+        pub fn show_message(&mut self, text: String) {
+            self.message = Some((text, Instant::now()));
+        }
+
+        pub fn record_frame_time(&mut self, seconds: f32) {
+            if self.frame_times.len() == FRAME_HISTORY {
+                self.frame_times.pop_front();
+            }
+            self.frame_times.push_back(seconds);
+        }
+
+        fn average_frame_time(&self) -> f32 {
+            if self.frame_times.is_empty() {
+                return 0.0;
+            }
+            self.frame_times.iter().sum::<f32>() / self.frame_times.len() as f32
+        }
+
+        pub fn ui(&self, ctx: &egui::Context, info: &HudInfo) {
+            // Feedback is shown even with the rest of the HUD hidden
+            if let Some((text, shown_at)) = &self.message {
+                if shown_at.elapsed().as_secs_f32() < MESSAGE_DURATION {
+                    egui::Area::new(egui::Id::new("hud_message"))
+                        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -90.0))
+                        .order(egui::Order::Foreground)
+                        .interactable(false)
+                        .show(ctx, |ui| {
+                            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                                ui.colored_label(Color32::from_rgb(255, 180, 80), text.as_str());
+                            });
+                        });
+                }
+            }
+
+            if !self.visible {
+                return;
+            }
+
+            // Crosshair in the middle of the screen, where the camera is aiming
+            let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("hud_crosshair")));
+            let center = ctx.screen_rect().center();
+            let size = 8.0;
+            for stroke in [Stroke::new(3.0, Color32::from_black_alpha(160)), Stroke::new(1.5, Color32::WHITE)] {
+                painter.line_segment([center - egui::vec2(size, 0.0), center + egui::vec2(size, 0.0)], stroke);
+                painter.line_segment([center - egui::vec2(0.0, size), center + egui::vec2(0.0, size)], stroke);
+            }
+
+            let frame_time = self.average_frame_time();
+            egui::Area::new(egui::Id::new("hud_info"))
+                .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-10.0, 40.0))
+                .order(egui::Order::Foreground)
+                .interactable(false)
+                .show(ctx, |ui| {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        let (x, y, z) = info.camera_position;
+                        let (cx, cy, cz) = info.camera_chunk;
+                        ui.monospace(format!("XYZ:   {:.2} / {:.2} / {:.2}", x, y, z));
+                        ui.monospace(format!("Chunk: {} / {} / {}", cx, cy, cz));
+                        match info.target {
+                            Some(((tx, ty, tz), voxel_type)) => {
+                                ui.monospace(format!("Target: {:?} at {} / {} / {}", voxel_type, tx, ty, tz));
+                            }
+                            None => {
+                                ui.monospace("Target: none");
+                            }
+                        }
+                        ui.monospace(format!("Mode:  {:?}", info.game_mode));
+                        match info.block_count {
+                            Some(count) => ui.monospace(format!("Block: {:?} x{}", info.selected_block, count)),
+                            None => ui.monospace(format!("Block: {:?}", info.selected_block)),
+                        };
+                        if frame_time > 0.0 {
+                            ui.monospace(format!("Frame: {:.2} ms ({:.0} FPS)", frame_time * 1000.0, 1.0 / frame_time));
+                        }
+                        ui.monospace(format!("Time:  {:.1} s, {} ticks this frame", info.elapsed_time, info.sim_ticks));
+                        if info.gamepads > 0 {
+                            ui.monospace(format!("Gamepads: {}", info.gamepads));
+                        }
+                        ui.weak(format!("{} - Toggle HUD", info.hud_keys));
+                    });
+                });
+        }
+    }
+
     pub struct BlockSelection {
         pub selected_block: VoxelType,
     }
@@ -118,6 +1013,11 @@ pub mod windows{
                 ui.radio_value(&mut self.selected_block, VoxelType::Wood, "Wood");
                 ui.radio_value(&mut self.selected_block, VoxelType::Leaves, "Leaves");
                 ui.radio_value(&mut self.selected_block, VoxelType::Light, "Light Block");
+                ui.radio_value(&mut self.selected_block, VoxelType::CoalOre, "Coal Ore");
+                ui.radio_value(&mut self.selected_block, VoxelType::IronOre, "Iron Ore");
+                ui.radio_value(&mut self.selected_block, VoxelType::GoldOre, "Gold Ore");
+                ui.radio_value(&mut self.selected_block, VoxelType::DiamondOre, "Diamond Ore");
+                ui.radio_value(&mut self.selected_block, VoxelType::Water, "Water");
             });
         }
     }
@@ -146,6 +1046,16 @@ pub mod windows{
         pub show_sandbox_window: bool,
         pub sandbox_window: &'a mut SandboxWindow,
         pub show_settings: bool,
+        pub show_terrain_preview: bool,
+        pub terrain_preview: TerrainPreviewWindow,
+        pub show_shape_tool: bool,
+        pub shape_tool: ShapeToolWindow,
+        pub show_camera_path: bool,
+        pub camera_path: CameraPathWindow,
+        pub show_controls: bool,
+        pub controls: ControlsWindow,
+        pub command_line: CommandLine,
+        pub hud: Hud,
     }
     
     impl<'a> MainWindow<'a> {
@@ -154,12 +1064,22 @@ pub mod windows{
                 show_sandbox_window: false,
                 sandbox_window,
                 show_settings: false,
+                show_terrain_preview: false,
+                terrain_preview: TerrainPreviewWindow::new(TerrainSettings::new()),
+                show_shape_tool: false,
+                shape_tool: ShapeToolWindow::new(),
+                show_camera_path: false,
+                camera_path: CameraPathWindow::new(),
+                show_controls: false,
+                controls: ControlsWindow::new(InputMap::new()),
+                command_line: CommandLine::new(),
+                hud: Hud::new(),
             }
         }
     
         pub fn ui(&mut self, ctx: &egui::Context) {
             self.desktop_ui(ctx);
-  
+            self.command_line.ui(ctx);
         }
     
         pub fn desktop_ui(&mut self, ctx: &egui::Context) {
@@ -177,7 +1097,39 @@ pub mod windows{
                             .resizable(true)
                             .default_width(400.0)
                             .show(ctx, |ui| {
-                                self.sandbox_window.ui(ctx, ui);
+                                self.sandbox_window.ui(ctx, ui, &self.controls.bindings);
+                            });
+                    }
+                    if self.show_terrain_preview {
+                        egui::Window::new("Terrain Preview")
+                            .resizable(true)
+                            .default_width(300.0)
+                            .show(ctx, |ui| {
+                                self.terrain_preview.ui(ui);
+                            });
+                    }
+                    if self.show_shape_tool {
+                        egui::Window::new("Shape Tools")
+                            .resizable(true)
+                            .default_width(250.0)
+                            .show(ctx, |ui| {
+                                self.shape_tool.ui(ui);
+                            });
+                    }
+                    if self.show_camera_path {
+                        egui::Window::new("Camera Path")
+                            .resizable(true)
+                            .default_width(300.0)
+                            .show(ctx, |ui| {
+                                self.camera_path.ui(ui);
+                            });
+                    }
+                    if self.show_controls {
+                        egui::Window::new("Controls")
+                            .resizable(true)
+                            .default_width(350.0)
+                            .show(ctx, |ui| {
+                                self.controls.ui(ui);
                             });
                     }
                     ui.hyperlink_to(
@@ -207,6 +1159,18 @@ pub mod windows{
                     if ui.button("Toggle Block Selection").clicked() {
                         self.show_sandbox_window = !self.show_sandbox_window;
                     }
+                    if ui.button("Toggle Terrain Preview").clicked() {
+                        self.show_terrain_preview = !self.show_terrain_preview;
+                    }
+                    if ui.button("Toggle Shape Tools").clicked() {
+                        self.show_shape_tool = !self.show_shape_tool;
+                    }
+                    if ui.button("Toggle Camera Path").clicked() {
+                        self.show_camera_path = !self.show_camera_path;
+                    }
+                    if ui.button("Toggle Controls").clicked() {
+                        self.show_controls = !self.show_controls;
+                    }
             
                     if ui.button("Organize windows").clicked() {
                         ui.ctx().memory_mut(|mem| mem.reset_areas());
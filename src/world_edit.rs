@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use crate::{VoxelType, World};
+
+// Maximum number of edits kept for undo
+const HISTORY_LIMIT: usize = 100;
+
+// A batch of voxel changes applied to the world as one undoable step
+pub struct WorldEdit {
+    changes: Vec<((i32, i32, i32), VoxelType, VoxelType)>, // Position, before, after
+    index: HashMap<(i32, i32, i32), usize>,               // Position -> entry in `changes`
+}
+
+impl WorldEdit {
+    pub fn new() -> Self {
+        Self { changes: Vec::new(), index: HashMap::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    // Change a voxel, remembering what was there the first time this edit touched it
+    pub fn set(&mut self, world: &mut World, position: (i32, i32, i32), voxel_type: VoxelType) {
+        let before = world.get_voxel(position.0, position.1, position.2);
+        match self.index.get(&position) {
+            Some(&i) => self.changes[i].2 = voxel_type,
+            None => {
+                if before == voxel_type {
+                    return;
+                }
+                self.index.insert(position, self.changes.len());
+                self.changes.push((position, before, voxel_type));
+            }
+        }
+        world.set_voxel(position.0, position.1, position.2, voxel_type);
+    }
+
+    fn undo(&self, world: &mut World) {
+        for &((x, y, z), before, _) in self.changes.iter().rev() {
+            world.set_voxel(x, y, z, before);
+        }
+    }
+
+    fn redo(&self, world: &mut World) {
+        for &((x, y, z), _, after) in &self.changes {
+            world.set_voxel(x, y, z, after);
+        }
+    }
+}
+
+// Undo and redo stacks of finished edits
+pub struct EditHistory {
+    undo_stack: Vec<WorldEdit>,
+    redo_stack: Vec<WorldEdit>,
+}
+
+impl EditHistory {
+    pub fn new() -> Self {
+        Self { undo_stack: Vec::new(), redo_stack: Vec::new() }
+    }
+
+    // Record an edit that has already been applied to the world
+    pub fn push(&mut self, edit: WorldEdit) {
+        if edit.is_empty() {
+            return;
+        }
+        if self.undo_stack.len() == HISTORY_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.undo_stack.push(edit);
+        self.redo_stack.clear();
+    }
+
+    pub fn undo(&mut self, world: &mut World) -> bool {
+        match self.undo_stack.pop() {
+            Some(edit) => {
+                edit.undo(world);
+                self.redo_stack.push(edit);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self, world: &mut World) -> bool {
+        match self.redo_stack.pop() {
+            Some(edit) => {
+                edit.redo(world);
+                self.undo_stack.push(edit);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Forget everything, e.g. when the world is regenerated
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn place(world: &mut World, position: (i32, i32, i32), voxel_type: VoxelType) -> WorldEdit {
+        let mut edit = WorldEdit::new();
+        edit.set(world, position, voxel_type);
+        edit
+    }
+
+    #[test]
+    fn undo_and_redo_walk_the_history() {
+        let mut world = World::new();
+        let mut history = EditHistory::new();
+        let first = place(&mut world, (0, 0, 0), VoxelType::Stone);
+        history.push(first);
+        let second = place(&mut world, (0, 0, 0), VoxelType::Wood);
+        history.push(second);
+
+        assert!(history.undo(&mut world));
+        assert_eq!(world.get_voxel(0, 0, 0), VoxelType::Stone);
+        assert!(history.undo(&mut world));
+        assert_eq!(world.get_voxel(0, 0, 0), VoxelType::Air);
+        assert!(!history.undo(&mut world));
+
+        assert!(history.redo(&mut world));
+        assert!(history.redo(&mut world));
+        assert_eq!(world.get_voxel(0, 0, 0), VoxelType::Wood);
+        assert!(!history.redo(&mut world));
+    }
+
+    #[test]
+    fn a_new_edit_drops_the_redo_stack() {
+        let mut world = World::new();
+        let mut history = EditHistory::new();
+        let edit = place(&mut world, (1, 0, 0), VoxelType::Stone);
+        history.push(edit);
+        history.undo(&mut world);
+        let edit = place(&mut world, (2, 0, 0), VoxelType::Dirt);
+        history.push(edit);
+        assert!(!history.redo(&mut world));
+        assert_eq!(world.get_voxel(1, 0, 0), VoxelType::Air);
+    }
+
+    #[test]
+    fn edits_restore_the_first_value_they_overwrote() {
+        let mut world = World::new();
+        world.set_voxel(0, 0, 0, VoxelType::Grass);
+        let mut edit = WorldEdit::new();
+        edit.set(&mut world, (0, 0, 0), VoxelType::Stone);
+        edit.set(&mut world, (0, 0, 0), VoxelType::Wood);
+        let mut history = EditHistory::new();
+        history.push(edit);
+        history.undo(&mut world);
+        assert_eq!(world.get_voxel(0, 0, 0), VoxelType::Grass);
+    }
+
+    #[test]
+    fn clear_and_empty_edits() {
+        let mut world = World::new();
+        let mut history = EditHistory::new();
+        history.push(WorldEdit::new());
+        assert!(!history.undo(&mut world));
+
+        let edit = place(&mut world, (0, 0, 0), VoxelType::Stone);
+        history.push(edit);
+        history.clear();
+        assert!(!history.undo(&mut world));
+        assert_eq!(world.get_voxel(0, 0, 0), VoxelType::Stone);
+    }
+}